@@ -1,4 +1,26 @@
-use crate::{Ray, Vec3};
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::{random, Image, Ray, Vec3};
+
+/// How a [Camera] maps the points of the image to the directions of its rays.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Projection {
+	/// Pinhole camera covering the vertical field of view, which keeps straight lines straight.
+	#[default]
+	Perspective,
+	/// Parallel rays from a viewport of `height` (in world units) around the camera position, without perspective
+	/// foreshortening, as used for architectural elevations.
+	Orthographic { height: f64 },
+	/// Equidistant fisheye: The angle between a ray and the viewing direction grows linearly with the distance from
+	/// the center of the image, reaching half the vertical field of view (up to 360°) at the top and bottom edge.
+	/// With 180°, a square image shows a dome projection.
+	Fisheye,
+	/// Latitude/longitude panorama covering 180° vertically and, in an image with an aspect ratio of 2:1, 360°
+	/// horizontally around the camera.
+	Equirectangular,
+}
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Camera {
@@ -10,88 +32,464 @@ pub struct Camera {
 	pub vertical: Vec3,
 	/// Upper left corner of the viewport
 	pub upper_left_corner: Vec3,
+	/// With the [Projection::Fisheye] and [Projection::Equirectangular] projections, the viewport is made of
+	/// angles: The lengths of `horizontal` and `vertical` are the angles in radians covered by the image.
+	pub projection: Projection,
+	/// Radius of the lens. Rays start at random points on the lens, so that only objects at the focus distance are
+	/// sharp. With a radius of 0.0, the camera is a pinhole camera and everything is sharp.
+	pub lens_radius: f64,
+	/// Distance from `look_from` to the plane (parallel to the viewport) that is in focus.
+	pub focus_distance: f64,
+	/// Time at which the shutter opens, between 0.0 and 1.0 like [Ray::time].
+	pub shutter_open: f64,
+	/// Time at which the shutter closes. Rays are spread over the time in between, so that moving objects are blurred
+	/// along their motion. If the shutter closes when it opens, all rays are at that time.
+	pub shutter_close: f64,
 }
 
 impl Camera {
-	/// Create a new camera.
-	/// 
-	/// # Arguments
-	/// 
-	/// * `aspect_ratio` - The aspect ratio of the viewport (0.0..).
-	/// * `vertical_fov` - The vertical field of view in degrees (0.0..360.0).
-	/// * `look_from` - The position of the camera / "eye".
-	/// * `look_at` - The position the camera is looking at.
-	/// * `vertical_up` - The up vector of the camera.
-	fn new(
-		aspect_ratio: f64,
-		vertical_fov: f64,
-		look_from: Vec3,
-		look_at: Vec3,
-		vertical_up: Vec3,
-	) -> Self {
-		assert!((0.0..).contains(&aspect_ratio));
-		assert!((0.0..360.0).contains(&vertical_fov));
-
-		let theta = vertical_fov.to_radians();
-		// Scalar value of the viewport height
-		let h = f64::tan(theta / 2.0);
-		let viewport_height = 2.0 * h;
-		let viewport_width = aspect_ratio * viewport_height;
-		dbg!(viewport_width, viewport_height);
-
-		let w = (look_from - look_at).unit_vector();
-		let u = -vertical_up.cross(w).unit_vector();
+	/// Returns a [CameraBuilder] with the parameters of [Camera::default].
+	pub fn builder() -> CameraBuilder {
+		CameraBuilder::default()
+	}
+
+	/// Returns the camera with a lens of the given `aperture` (diameter) focused at `focus_distance`, which blurs
+	/// everything in front of and behind the focus plane (depth of field). An aperture of 0.0 gives a pinhole camera.
+	pub fn with_defocus(&self, aperture: f64, focus_distance: f64) -> Camera {
+		Camera {
+			lens_radius: aperture / 2.0,
+			focus_distance,
+			..self.clone()
+		}
+	}
+
+	/// Returns the camera with its viewport widened or narrowed to the `aspect_ratio` (width / height), keeping the
+	/// vertical field of view and the viewing direction. Fails with [CameraError::AspectRatio] if the ratio is not
+	/// positive.
+	pub fn with_aspect_ratio(&self, aspect_ratio: f64) -> Result<Camera, CameraError> {
+		if !(aspect_ratio.is_finite() && aspect_ratio > 0.0) {
+			return Err(CameraError::AspectRatio);
+		}
+		let viewport_center = self.upper_left_corner + self.horizontal / 2.0 + self.vertical / 2.0;
+		let horizontal = self.horizontal.unit_vector() * (self.vertical.length() * aspect_ratio);
+		Ok(Camera {
+			horizontal,
+			upper_left_corner: viewport_center - horizontal / 2.0 - self.vertical / 2.0,
+			..self.clone()
+		})
+	}
+
+	/// Returns a ray that starts at the camera's origin and goes through the pixel in the viewport, offset by the
+	/// given horizontal and vertical scalars (between 0.0 and 1.0), at a random time while the shutter is open.
+	pub fn ray(&self, horizontal_scalar: f64, vertical_scalar: f64) -> Ray {
+		assert!((-0.05..=1.05).contains(&horizontal_scalar));
+		assert!((-0.05..=1.05).contains(&vertical_scalar));
+
+		let time = if self.shutter_close > self.shutter_open {
+			random::gen_range(self.shutter_open..self.shutter_close)
+		} else {
+			self.shutter_open
+		};
+
+		// Offset vectors from the lower upper left corner into the pixel of the viewport
+		let horizontal_offset = self.horizontal * horizontal_scalar;
+		let vertical_offset = self.vertical * vertical_scalar;
+		let pinhole_ray = match self.projection {
+			Projection::Perspective => Ray {
+				origin: self.look_from,
+				direction: {
+					// A direction vector is always calculated from target minus start
+					let target = self.upper_left_corner + horizontal_offset + vertical_offset;
+					let start = self.look_from;
+					target - start
+				},
+				time,
+			},
+			Projection::Orthographic { .. } => Ray {
+				origin: self.upper_left_corner + horizontal_offset + vertical_offset,
+				direction: self.view_direction(),
+				time,
+			},
+			Projection::Fisheye | Projection::Equirectangular => {
+				let (right, up, forward) =
+					(self.horizontal.unit_vector(), -self.vertical.unit_vector(), self.view_direction());
+				// Angles from the center of the image to the right and upwards
+				let x = (horizontal_scalar - 0.5) * self.horizontal.length();
+				let y = (0.5 - vertical_scalar) * self.vertical.length();
+				let direction = if self.projection == Projection::Fisheye {
+					// Tilted from the viewing direction by the distance from the center
+					let angle = x.hypot(y);
+					if angle == 0.0 {
+						forward
+					} else {
+						forward * angle.cos() + (right * x + up * y) * (angle.sin() / angle)
+					}
+				} else {
+					// Longitude x and latitude y
+					(forward * x.cos() + right * x.sin()) * y.cos() + up * y.sin()
+				};
+				Ray { origin: self.look_from, direction, time }
+			}
+		};
+		if self.lens_radius <= 0.0 {
+			return pinhole_ray;
+		}
+
+		// The viewport is at distance 1.0, so the pinhole ray reaches the focus plane at the focus distance. All
+		// rays through the lens meet there, which keeps that plane sharp.
+		let focus_point = pinhole_ray.at(self.focus_distance);
+		let lens_point = Vec3::random_in_unit_disk() * self.lens_radius;
+		let origin = pinhole_ray.origin
+			+ self.horizontal.unit_vector() * lens_point.x
+			+ self.vertical.unit_vector() * lens_point.y;
+		Ray { origin, direction: focus_point - origin, time }
+	}
+
+	/// Returns the unit vector in the viewing direction, i.e. towards the center of the viewport.
+	fn view_direction(&self) -> Vec3 {
+		self.horizontal.cross(self.vertical).unit_vector()
+	}
+}
+
+impl Default for Camera {
+	/// Camera with a 16:9 viewport and a vertical field of view of 90° looking from (-2, 2, 1) to (0, 0, -1).
+	fn default() -> Self {
+		Camera::builder().build().expect("The default parameters are valid")
+	}
+}
+
+/// Builder for a [Camera], validating its parameters.
+///
+/// ```
+/// # use raytracer::{Camera, Image, Vec3};
+/// let image = Image::new(800, 800);
+/// let camera = Camera::builder()
+///     .look_from(Vec3 { x: 0.0, y: 1.0, z: 5.0 })
+///     .look_at(Vec3 { x: 0.0, y: 0.0, z: 0.0 })
+///     .vertical_fov(40.0)
+///     .aspect_ratio_of(&image)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraBuilder {
+	aspect_ratio: f64,
+	projection: Projection,
+	vertical_fov: f64,
+	look_from: Vec3,
+	look_at: Vec3,
+	vertical_up: Vec3,
+	aperture: f64,
+	focus_distance: Option<f64>,
+	shutter: (f64, f64),
+}
+
+impl Default for CameraBuilder {
+	fn default() -> Self {
+		Self {
+			aspect_ratio: 16.0 / 9.0,
+			projection: Projection::Perspective,
+			vertical_fov: 90.0,
+			look_from: Vec3 { x: -2.0, y: 2.0, z: 1.0 },
+			look_at: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			vertical_up: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			aperture: 0.0,
+			focus_distance: None,
+			shutter: (0.0, 0.0),
+		}
+	}
+}
+
+impl CameraBuilder {
+	/// Sets the aspect ratio (width / height) of the viewport, which has to match that of the rendered image.
+	pub fn aspect_ratio(mut self, aspect_ratio: f64) -> Self {
+		self.aspect_ratio = aspect_ratio;
+		self
+	}
+
+	/// Sets the aspect ratio of the viewport to that of the `image` the camera renders into.
+	pub fn aspect_ratio_of(self, image: &Image) -> Self {
+		self.aspect_ratio(image.aspect_ratio())
+	}
+
+	/// Sets how the image is projected, defaults to [Projection::Perspective].
+	pub fn projection(mut self, projection: Projection) -> Self {
+		self.projection = projection;
+		self
+	}
+
+	/// Sets the vertical field of view in degrees, between 0° and 180° (exclusive) for the perspective projection
+	/// and up to 360° for the fisheye projection. The other projections ignore it.
+	pub fn vertical_fov(mut self, degrees: f64) -> Self {
+		self.vertical_fov = degrees;
+		self
+	}
+
+	/// Sets the position of the camera.
+	pub fn look_from(mut self, look_from: Vec3) -> Self {
+		self.look_from = look_from;
+		self
+	}
+
+	/// Sets the point in the center of the view.
+	pub fn look_at(mut self, look_at: Vec3) -> Self {
+		self.look_at = look_at;
+		self
+	}
+
+	/// Sets the direction that appears upwards in the image. It doesn't need to be perpendicular to the viewing
+	/// direction, but must not be parallel to it.
+	pub fn vertical_up(mut self, vertical_up: Vec3) -> Self {
+		self.vertical_up = vertical_up;
+		self
+	}
+
+	/// Sets the diameter of the lens, see [Camera::with_defocus]. Defaults to 0.0, a pinhole camera.
+	pub fn aperture(mut self, aperture: f64) -> Self {
+		self.aperture = aperture;
+		self
+	}
+
+	/// Sets the distance of the plane in focus. Defaults to the distance between `look_from` and `look_at`.
+	pub fn focus_distance(mut self, focus_distance: f64) -> Self {
+		self.focus_distance = Some(focus_distance);
+		self
+	}
+
+	/// Sets the times at which the shutter opens and closes, with `0.0 <= open <= close <= 1.0`. Defaults to an
+	/// instant shutter at 0.0, without motion blur.
+	pub fn shutter(mut self, open: f64, close: f64) -> Self {
+		self.shutter = (open, close);
+		self
+	}
+
+	/// Validates the parameters and creates the camera.
+	pub fn build(&self) -> Result<Camera, CameraError> {
+		if !(self.aspect_ratio.is_finite() && self.aspect_ratio > 0.0) {
+			return Err(CameraError::AspectRatio);
+		}
+		// Size of the viewport, at distance 1.0 from the camera for the perspective projection
+		let viewport_height = match self.projection {
+			Projection::Perspective => {
+				if !(self.vertical_fov > 0.0 && self.vertical_fov < 180.0) {
+					return Err(CameraError::VerticalFov);
+				}
+				2.0 * f64::tan(self.vertical_fov.to_radians() / 2.0)
+			}
+			Projection::Orthographic { height } => {
+				if !(height.is_finite() && height > 0.0) {
+					return Err(CameraError::ViewportHeight);
+				}
+				height
+			}
+			Projection::Fisheye => {
+				if !(self.vertical_fov > 0.0 && self.vertical_fov <= 360.0) {
+					return Err(CameraError::VerticalFov);
+				}
+				self.vertical_fov.to_radians()
+			}
+			Projection::Equirectangular => PI,
+		};
+		let viewport_width = self.aspect_ratio * viewport_height;
+		let view_direction = self.look_at - self.look_from;
+		if view_direction.is_near_zero() {
+			return Err(CameraError::LookAt);
+		}
+		if self.vertical_up.cross(view_direction).is_near_zero() {
+			return Err(CameraError::VerticalUp);
+		}
+		if !(self.aperture.is_finite() && self.aperture >= 0.0) {
+			return Err(CameraError::Aperture);
+		}
+		let focus_distance = self.focus_distance.unwrap_or(view_direction.length());
+		if !(focus_distance.is_finite() && focus_distance > 0.0) {
+			return Err(CameraError::FocusDistance);
+		}
+		let (shutter_open, shutter_close) = self.shutter;
+		if !(0.0 <= shutter_open && shutter_open <= shutter_close && shutter_close <= 1.0) {
+			return Err(CameraError::Shutter);
+		}
+
+		// Orthonormal basis of the camera: w points backwards, u to the right and v upwards
+		let w = -view_direction.unit_vector();
+		let u = self.vertical_up.cross(w).unit_vector();
 		let v = w.cross(u);
 
-		// Eye or camera center
-		// Offset vectors from the lower upper left corner of the viewport
+		// Offset vectors from the upper left corner of the viewport to the right and downwards
 		let horizontal = u * viewport_width;
-		let vertical = v * viewport_height;
-		// "Origin" of the viewport
+		let vertical = -v * viewport_height;
 		let upper_left_corner = {
-			let viewport_center = look_from - w;
+			// The orthographic viewport goes through the camera, from where its rays start
+			let viewport_center = match self.projection {
+				Projection::Orthographic { .. } => self.look_from,
+				_ => self.look_from - w,
+			};
 			viewport_center
 				- horizontal / 2.0 // half a screen to the left
 				- vertical / 2.0 // half a screen to the top
 		};
 
-		Self {
-			look_from,
+		Ok(Camera {
+			look_from: self.look_from,
 			horizontal,
 			vertical,
 			upper_left_corner,
+			projection: self.projection,
+			lens_radius: self.aperture / 2.0,
+			focus_distance,
+			shutter_open,
+			shutter_close,
+		})
+	}
+}
+
+/// Invalid parameter of a [CameraBuilder].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraError {
+	/// The aspect ratio is not positive.
+	AspectRatio,
+	/// The vertical field of view is not between 0° and 180°, or 360° for the fisheye projection.
+	VerticalFov,
+	/// The height of the orthographic viewport is not positive.
+	ViewportHeight,
+	/// The camera looks at its own position.
+	LookAt,
+	/// The up direction is zero or parallel to the viewing direction.
+	VerticalUp,
+	/// The aperture is negative.
+	Aperture,
+	/// The focus distance is not positive.
+	FocusDistance,
+	/// The shutter doesn't open and close in this order between the times 0.0 and 1.0.
+	Shutter,
+}
+
+impl fmt::Display for CameraError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			CameraError::AspectRatio => "aspect ratio must be positive",
+			CameraError::VerticalFov => {
+				"vertical field of view must be between 0 and 180 degrees (exclusive), or 360 degrees for a fisheye"
+			}
+			CameraError::ViewportHeight => "orthographic viewport height must be positive",
+			CameraError::LookAt => "look_at must differ from look_from",
+			CameraError::VerticalUp => "vertical_up must not be zero or parallel to the viewing direction",
+			CameraError::Aperture => "aperture must be finite and not negative",
+			CameraError::FocusDistance => "focus distance must be positive",
+			CameraError::Shutter => "shutter must open before it closes, both between the times 0.0 and 1.0",
+		})
+	}
+}
+
+impl Error for CameraError {}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn with_aspect_ratio() {
+		let camera = Camera::default();
+		let square = camera.with_aspect_ratio(1.0).unwrap();
+		assert!((square.horizontal.length() - square.vertical.length()).abs() < 1e-9);
+		// The center of the viewport doesn't move
+		assert!((square.ray(0.5, 0.5).direction - camera.ray(0.5, 0.5).direction).is_near_zero());
+		for aspect_ratio in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+			assert_eq!(camera.with_aspect_ratio(aspect_ratio), Err(CameraError::AspectRatio));
 		}
 	}
 
-	/// Returns a ray that starts at the camera's origin and goes through the pixel in the viewport, offset by the
-	/// given horizontal and vertical scalars (between 0.0 and 1.0).
-	pub fn ray(&self, horizontal_scalar: f64, vertical_scalar: f64) -> Ray {
-		assert!(horizontal_scalar >= -0.05 && horizontal_scalar <= 1.05);
-		assert!(vertical_scalar >= -0.05 && vertical_scalar <= 1.05);
+	#[test]
+	fn builder() {
+		let image = Image::new(300, 200);
+		let camera = Camera::builder().aspect_ratio_of(&image).vertical_fov(60.0).build().unwrap();
+		assert!((camera.horizontal.length() / camera.vertical.length() - 1.5).abs() < 1e-9);
+		assert!((camera.vertical.length() - 2.0 * 30f64.to_radians().tan()).abs() < 1e-9);
 
-		// Offset vectors from the lower upper left corner into the pixel of the viewport
-		let horizontal_offset = self.horizontal * horizontal_scalar;
-		let vertical_offset = self.vertical * vertical_scalar;
-		Ray {
-			origin: self.look_from,
-			direction: {
-				// A direction vector is always calculated from target minus start
-				let target = self.upper_left_corner + horizontal_offset + vertical_offset;
-				let start = self.look_from;
-				target - start
-			},
+		let builder = Camera::builder();
+		assert_eq!(builder.clone().aspect_ratio(0.0).build(), Err(CameraError::AspectRatio));
+		assert_eq!(builder.clone().vertical_fov(180.0).build(), Err(CameraError::VerticalFov));
+		assert!(builder.clone().vertical_fov(180.0).projection(Projection::Fisheye).build().is_ok());
+		let orthographic = builder.clone().projection(Projection::Orthographic { height: 0.0 });
+		assert_eq!(orthographic.build(), Err(CameraError::ViewportHeight));
+		assert_eq!(builder.clone().look_at(Vec3 { x: -2.0, y: 2.0, z: 1.0 }).build(), Err(CameraError::LookAt));
+		assert_eq!(builder.clone().vertical_up(Vec3 { x: 2.0, y: -2.0, z: -2.0 }).build(), Err(CameraError::VerticalUp));
+		assert_eq!(builder.clone().aperture(-1.0).build(), Err(CameraError::Aperture));
+		assert_eq!(builder.clone().focus_distance(0.0).build(), Err(CameraError::FocusDistance));
+		assert_eq!(builder.shutter(0.5, 0.25).build(), Err(CameraError::Shutter));
+	}
+
+	#[test]
+	fn orientation() {
+		let camera = Camera::builder()
+			.look_from(Vec3 { x: 0.0, y: 0.0, z: 0.0 })
+			.look_at(Vec3 { x: 0.0, y: 0.0, z: -1.0 })
+			.build()
+			.unwrap();
+		// Looking along -z with +y up, +x is on the right
+		let upper_left = camera.ray(0.0, 0.0).direction;
+		assert!(upper_left.x < 0.0 && upper_left.y > 0.0);
+		let lower_right = camera.ray(1.0, 1.0).direction;
+		assert!(lower_right.x > 0.0 && lower_right.y < 0.0);
+	}
+
+	#[test]
+	fn projections() {
+		let builder = Camera::builder()
+			.look_from(Vec3 { x: 0.0, y: 0.0, z: 0.0 })
+			.look_at(Vec3 { x: 0.0, y: 0.0, z: -1.0 })
+			.aspect_ratio(2.0);
+		let near = |a: Vec3, b: Vec3| (a.unit_vector() - b.unit_vector()).length() < 1e-9;
+		let forward = Vec3 { x: 0.0, y: 0.0, z: -1.0 };
+
+		let orthographic = builder.clone().projection(Projection::Orthographic { height: 4.0 }).build().unwrap();
+		let ray = orthographic.ray(0.0, 0.0);
+		assert!(near(ray.direction, forward));
+		assert!((ray.origin - Vec3 { x: -4.0, y: 2.0, z: 0.0 }).is_near_zero());
+
+		let fisheye = builder.clone().projection(Projection::Fisheye).vertical_fov(180.0).build().unwrap();
+		assert!(near(fisheye.ray(0.5, 0.5).direction, forward));
+		assert!(near(fisheye.ray(0.5, 0.0).direction, Vec3 { x: 0.0, y: 1.0, z: 0.0 }));
+		// Twice as wide as high, so the sides are 180° from the viewing direction
+		assert!(near(fisheye.ray(1.0, 0.5).direction, -forward));
+		assert!(near(fisheye.ray(0.75, 0.5).direction, Vec3 { x: 1.0, y: 0.0, z: 0.0 }));
+
+		let panorama = builder.projection(Projection::Equirectangular).build().unwrap();
+		assert!(near(panorama.ray(0.5, 0.5).direction, forward));
+		assert!(near(panorama.ray(0.75, 0.5).direction, Vec3 { x: 1.0, y: 0.0, z: 0.0 }));
+		assert!(near(panorama.ray(0.0, 0.5).direction, -forward));
+		assert!(near(panorama.ray(0.3, 1.0).direction, Vec3 { x: 0.0, y: -1.0, z: 0.0 }));
+	}
+
+	#[test]
+	fn defocus() {
+		let camera = Camera::default();
+		// Without aperture, the rays are those of the pinhole camera
+		assert_eq!(camera.with_defocus(0.0, 2.0).ray(0.3, 0.6), camera.ray(0.3, 0.6));
+
+		random::seed(1);
+		let blurry = camera.with_defocus(0.5, 2.0);
+		let pinhole_ray = camera.ray(0.3, 0.6);
+		for _ in 0..10 {
+			let ray = blurry.ray(0.3, 0.6);
+			assert!((ray.origin - camera.look_from).length() < 0.25);
+			// Every ray passes through the same point on the focus plane
+			let focus_point = pinhole_ray.at(2.0);
+			assert!((ray.at(1.0) - focus_point).is_near_zero());
 		}
+		assert_ne!(blurry.ray(0.3, 0.6).origin, blurry.ray(0.3, 0.6).origin);
 	}
-}
 
-impl Default for Camera {
-	fn default() -> Self {
-		Self::new(
-			16.0 / 9.0,
-			90.0,
-			Vec3 { x: -2.0, y: 2.0, z: 1.0 },
-			Vec3 { x: 0.0, y: 0.0, z: -1.0 },
-			Vec3 { x: 0.0, y: 1.0, z: 0.0 },
-		)
-	}
-}
\ No newline at end of file
+	#[test]
+	fn shutter() {
+		let camera = Camera::default();
+		assert_eq!(camera.ray(0.5, 0.5).time, 0.0);
+
+		random::seed(1);
+		let camera = Camera::builder().shutter(0.25, 0.75).build().unwrap();
+		let times: Vec<f64> = (0..10).map(|_| camera.ray(0.5, 0.5).time).collect();
+		assert!(times.iter().all(|time| (0.25..0.75).contains(time)));
+		assert_ne!(times[0], times[1]);
+	}
+}
@@ -0,0 +1,235 @@
+//! Writer for OpenEXR images, which store linear high dynamic range data in any number of named channels.
+//!
+//! Only single-part scanline images without compression are written, which every OpenEXR reader supports.
+
+use std::io;
+
+/// Data type of the values stored in an OpenEXR channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SampleType {
+	/// 16-bit floating point numbers, which are precise enough for colors and half as big as [SampleType::Float].
+	#[default]
+	Half,
+	/// 32-bit floating point numbers.
+	Float,
+}
+
+impl SampleType {
+	/// Value identifying the type in the channel list.
+	fn id(&self) -> i32 {
+		match self {
+			SampleType::Half => 1,
+			SampleType::Float => 2,
+		}
+	}
+
+	/// Size of a single value in bytes.
+	fn size(&self) -> usize {
+		match self {
+			SampleType::Half => 2,
+			SampleType::Float => 4,
+		}
+	}
+}
+
+/// A named channel of an image, e.g. `R` for the red component or `albedo.R` for the red component of the layer
+/// `albedo`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+	pub name: String,
+	pub sample_type: SampleType,
+	/// One value per pixel, row by row from top to bottom.
+	pub values: Vec<f32>,
+}
+
+/// Writes the `channels` as OpenEXR image with the given dimensions to the `writer`.
+///
+/// # Errors
+///
+/// If the image is empty, a channel has the wrong number of values, or two channels have the same name.
+pub fn write(writer: &mut impl io::Write, width: usize, height: usize, channels: &[Channel]) -> io::Result<()> {
+	let invalid_input = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_string());
+	if width == 0 || height == 0 || channels.is_empty() {
+		return Err(invalid_input("OpenEXR images must have at least one pixel and one channel"));
+	}
+	if channels.iter().any(|channel| channel.values.len() != width * height) {
+		return Err(invalid_input("Every channel must have exactly one value per pixel"));
+	}
+	let (max_x, max_y) = match (i32::try_from(width - 1), i32::try_from(height - 1)) {
+		(Ok(max_x), Ok(max_y)) => (max_x, max_y),
+		_ => return Err(invalid_input("Image is too large for OpenEXR")),
+	};
+
+	// The channel list and the pixel data have to be sorted by channel name
+	let mut channels: Vec<&Channel> = channels.iter().collect();
+	channels.sort_by(|channel1, channel2| channel1.name.cmp(&channel2.name));
+	if channels.windows(2).any(|pair| pair[0].name == pair[1].name) {
+		return Err(invalid_input("Channel names must be unique"));
+	}
+
+	let mut header = Vec::new();
+	// Magic number and version 2 without any flags, i.e. a single-part scanline image
+	header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+
+	let mut channel_list = Vec::new();
+	for channel in &channels {
+		channel_list.extend_from_slice(channel.name.as_bytes());
+		channel_list.push(0);
+		channel_list.extend_from_slice(&channel.sample_type.id().to_le_bytes());
+		// Not perceptually linear, three reserved bytes, x and y sampling rate of 1
+		channel_list.extend_from_slice(&[0, 0, 0, 0]);
+		channel_list.extend_from_slice(&1i32.to_le_bytes());
+		channel_list.extend_from_slice(&1i32.to_le_bytes());
+	}
+	channel_list.push(0);
+	write_attribute(&mut header, "channels", "chlist", &channel_list);
+
+	// No compression
+	write_attribute(&mut header, "compression", "compression", &[0]);
+	let window: Vec<u8> = [0, 0, max_x, max_y].iter().flat_map(|value| value.to_le_bytes()).collect();
+	write_attribute(&mut header, "dataWindow", "box2i", &window);
+	write_attribute(&mut header, "displayWindow", "box2i", &window);
+	// Increasing y, i.e. from top to bottom
+	write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+	write_attribute(&mut header, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+	write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+	write_attribute(&mut header, "screenWindowWidth", "float", &1f32.to_le_bytes());
+	// End of header
+	header.push(0);
+	writer.write_all(&header)?;
+
+	// Offset table with the position of every scanline in the file. Each scanline is a chunk consisting of its
+	// y coordinate, its size and the values of all channels.
+	let scanline_size: usize = channels.iter().map(|channel| channel.sample_type.size() * width).sum();
+	let chunk_size = 4 + 4 + scanline_size;
+	let first_chunk_offset = header.len() + height * 8;
+	for y in 0..height {
+		writer.write_all(&((first_chunk_offset + y * chunk_size) as u64).to_le_bytes())?;
+	}
+
+	let mut scanline = Vec::with_capacity(chunk_size);
+	for y in 0..height {
+		scanline.clear();
+		scanline.extend_from_slice(&(y as i32).to_le_bytes());
+		scanline.extend_from_slice(&(scanline_size as i32).to_le_bytes());
+		for channel in &channels {
+			for &value in &channel.values[y * width..(y + 1) * width] {
+				match channel.sample_type {
+					SampleType::Half => scanline.extend_from_slice(&f32_to_f16(value).to_le_bytes()),
+					SampleType::Float => scanline.extend_from_slice(&value.to_le_bytes()),
+				}
+			}
+		}
+		writer.write_all(&scanline)?;
+	}
+
+	Ok(())
+}
+
+/// Appends a header attribute with its name, type name, size and value to the `header`.
+fn write_attribute(header: &mut Vec<u8>, name: &str, type_name: &str, value: &[u8]) {
+	header.extend_from_slice(name.as_bytes());
+	header.push(0);
+	header.extend_from_slice(type_name.as_bytes());
+	header.push(0);
+	header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+	header.extend_from_slice(value);
+}
+
+/// Converts a 32-bit float to the bits of a 16-bit float (IEEE 754 binary16), rounding to the nearest
+/// representable value. Values too large for a half become infinity.
+fn f32_to_f16(value: f32) -> u16 {
+	let bits = value.to_bits();
+	let sign = ((bits >> 16) & 0x8000) as u16;
+	let exponent = ((bits >> 23) & 0xff) as i32;
+	let mantissa = bits & 0x007f_ffff;
+
+	if exponent == 0xff {
+		// Infinity stays infinity, NaN stays NaN
+		return sign | 0x7c00 | if mantissa != 0 { 0x0200 } else { 0 };
+	}
+
+	// Rebias the exponent from 127 to 15
+	let half_exponent = exponent - 127 + 15;
+	if half_exponent >= 0x1f {
+		return sign | 0x7c00;
+	}
+
+	// Rounds `mantissa >> shift` to the nearest value, ties to even
+	let round_shifted = |mantissa: u32, shift: u32| {
+		let shifted = mantissa >> shift;
+		let remainder = mantissa & ((1 << shift) - 1);
+		let halfway = 1 << (shift - 1);
+		if remainder > halfway || (remainder == halfway && shifted & 1 == 1) {
+			shifted + 1
+		} else {
+			shifted
+		}
+	};
+
+	if half_exponent <= 0 {
+		// Too small for a normalized half, so it becomes subnormal (or zero). The implicit leading one of the
+		// float mantissa has to be made explicit.
+		if half_exponent < -10 {
+			return sign;
+		}
+		let shift = (14 - half_exponent) as u32;
+		return sign | round_shifted(mantissa | 0x0080_0000, shift) as u16;
+	}
+
+	// Rounding up may carry over into the exponent, which is still correct (up to infinity)
+	sign | round_shifted(((half_exponent as u32) << 23) | mantissa, 13) as u16
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn half_conversion() {
+		assert_eq!(f32_to_f16(0.0), 0x0000);
+		assert_eq!(f32_to_f16(-0.0), 0x8000);
+		assert_eq!(f32_to_f16(1.0), 0x3c00);
+		assert_eq!(f32_to_f16(0.5), 0x3800);
+		assert_eq!(f32_to_f16(-2.0), 0xc000);
+		assert_eq!(f32_to_f16(65504.0), 0x7bff);
+		assert_eq!(f32_to_f16(1e6), 0x7c00);
+		assert_eq!(f32_to_f16(f32::INFINITY), 0x7c00);
+		assert_eq!(f32_to_f16(f32::NAN) & 0x7c00, 0x7c00);
+		// Smallest subnormal half
+		assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+		// 1 + 2^-11 lies exactly between two halves and is rounded to the even one
+		assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+		assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+	}
+
+	#[test]
+	fn write_exr() {
+		let channels = [
+			Channel { name: "R".to_string(), sample_type: SampleType::Half, values: vec![1.0, 2.0] },
+			Channel { name: "A".to_string(), sample_type: SampleType::Float, values: vec![0.5, 0.25] },
+		];
+		let mut exr = Vec::new();
+		write(&mut exr, 2, 1, &channels).unwrap();
+
+		assert_eq!(&exr[..4], &[0x76, 0x2f, 0x31, 0x01]);
+		// The only scanline is at the end: y, size, sorted channels A (2 floats) and R (2 halves)
+		let scanline_size = 2 * 4 + 2 * 2;
+		let chunk = &exr[exr.len() - (8 + scanline_size)..];
+		let offset = u64::from_le_bytes(exr[exr.len() - (8 + scanline_size) - 8..][..8].try_into().unwrap());
+		assert_eq!(offset as usize, exr.len() - chunk.len());
+		assert_eq!(&chunk[..8], &[0, 0, 0, 0, scanline_size as u8, 0, 0, 0]);
+		assert_eq!(&chunk[8..12], &0.5f32.to_le_bytes());
+		assert_eq!(&chunk[12..16], &0.25f32.to_le_bytes());
+		assert_eq!(&chunk[16..], &[0x00, 0x3c, 0x00, 0x40]);
+	}
+
+	#[test]
+	fn invalid_channels() {
+		let channel = Channel { name: "R".to_string(), sample_type: SampleType::Half, values: vec![1.0] };
+		assert!(write(&mut Vec::new(), 2, 1, std::slice::from_ref(&channel)).is_err());
+		assert!(write(&mut Vec::new(), 1, 1, &[channel.clone(), channel]).is_err());
+		assert!(write(&mut Vec::new(), 1, 1, &[]).is_err());
+	}
+}
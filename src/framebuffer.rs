@@ -0,0 +1,285 @@
+use std::io;
+
+use crate::{exr, image, Image, Rgb, TransferFunction};
+
+/// Image of linear radiance values, as produced by the renderer.
+///
+/// Unlike [Image], it stores the unclamped floating point values, so that bright highlights are preserved for tone
+/// mapping and high dynamic range file formats.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Framebuffer {
+	pub rows: Vec<Vec<Rgb>>,
+}
+
+impl Framebuffer {
+	/// Creates a new black framebuffer with the given dimensions.
+	pub fn new(width: usize, height: usize) -> Self {
+		Self {
+			rows: vec![vec![Rgb::BLACK; width]; height],
+		}
+	}
+
+	/// Returns the width of the framebuffer.
+	pub fn width(&self) -> usize {
+		match self.rows.first() {
+			Some(row) => row.len(),
+			None => 0,
+		}
+	}
+
+	/// Returns the height of the framebuffer.
+	pub fn height(&self) -> usize {
+		self.rows.len()
+	}
+
+	/// Returns an iterator over all pixels, row by row.
+	pub fn pixels(&self) -> impl Iterator<Item = &Rgb> {
+		self.rows.iter().flatten()
+	}
+
+	/// Adds the pixels of `other`, weighted by `weight`, to the pixels of this framebuffer. This allows to
+	/// accumulate multiple render passes.
+	///
+	/// # Panics
+	///
+	/// If the dimensions of the framebuffers differ.
+	pub fn accumulate(&mut self, other: &Framebuffer, weight: f64) {
+		assert_eq!((self.width(), self.height()), (other.width(), other.height()), "Framebuffer dimensions differ");
+		for (row, other_row) in self.rows.iter_mut().zip(&other.rows) {
+			for (pixel, other_pixel) in row.iter_mut().zip(other_row) {
+				*pixel += *other_pixel * weight;
+			}
+		}
+	}
+
+	/// Converts the framebuffer to an 8-bit [Image], encoding the pixels with the `transfer` function.
+	pub fn to_image(&self, transfer: TransferFunction) -> Image {
+		Image {
+			rows: self.rows.iter()
+				.map(|row| row.iter().map(|pixel| pixel.to_color(transfer)).collect())
+				.collect(),
+		}
+	}
+
+	/// Writes the framebuffer as 16-bit RGB Portable Network Graphics (PNG) to the given writer, encoding the pixels
+	/// with the `transfer` function.
+	pub fn write_png16(&self, writer: &mut impl io::Write, transfer: TransferFunction) -> io::Result<()> {
+		let quantize = |component: f64| {
+			((transfer.encode(component).clamp(0.0, 1.0) * u16::MAX as f64).round() as u16).to_be_bytes()
+		};
+		let data: Vec<u8> = self.pixels()
+			.flat_map(|pixel| [pixel.r, pixel.g, pixel.b])
+			.flat_map(quantize)
+			.collect();
+		image::write_png(writer, self.width(), self.height(), png::ColorType::Rgb, png::BitDepth::Sixteen, &data)
+	}
+
+	/// Returns the red, green and blue channels of the framebuffer for [exr::write]. The channels are named `R`, `G`
+	/// and `B`, prefixed by `layer` and a dot unless `layer` is empty, e.g. `albedo.R`.
+	pub fn exr_channels(&self, layer: &str, sample_type: exr::SampleType) -> [exr::Channel; 3] {
+		let channel = |name: &str, component: fn(&Rgb) -> f64| exr::Channel {
+			name: if layer.is_empty() { name.to_string() } else { format!("{}.{}", layer, name) },
+			sample_type,
+			values: self.pixels().map(|pixel| component(pixel) as f32).collect(),
+		};
+		[
+			channel("R", |pixel| pixel.r),
+			channel("G", |pixel| pixel.g),
+			channel("B", |pixel| pixel.b),
+		]
+	}
+
+	/// Writes the linear radiance of the framebuffer as OpenEXR with red, green and blue channels to the given
+	/// writer. Use [exr::write] with [Framebuffer::exr_channels] to store multiple framebuffers in one file.
+	pub fn write_exr(&self, writer: &mut impl io::Write, sample_type: exr::SampleType) -> io::Result<()> {
+		exr::write(writer, self.width(), self.height(), &self.exr_channels("", sample_type))
+	}
+
+	/// Writes the linear radiance of the framebuffer as Portable Float Map (PFM) with 32-bit floats to the given
+	/// writer.
+	pub fn write_pfm(&self, writer: &mut impl io::Write) -> io::Result<()> {
+		// Header. The negative scale indicates little endian data.
+		writer.write_fmt(format_args!("PF\n{} {}\n-1.0\n", self.width(), self.height()))?;
+
+		// Image data, from the bottom row to the top row
+		for row in self.rows.iter().rev() {
+			for pixel in row {
+				for component in [pixel.r, pixel.g, pixel.b] {
+					writer.write_all(&(component as f32).to_le_bytes())?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Writes the linear radiance of the framebuffer as Radiance HDR (RGBE) to the given writer.
+	///
+	/// Each pixel is stored as 8-bit mantissas for red, green and blue with a shared 8-bit exponent. Scanlines are
+	/// run-length encoded.
+	pub fn write_radiance_hdr(&self, writer: &mut impl io::Write) -> io::Result<()> {
+		// Header
+		writer.write_all(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n")?;
+		writer.write_fmt(format_args!("-Y {} +X {}\n", self.height(), self.width()))?;
+
+		// Image data
+		for row in &self.rows {
+			let pixels: Vec<[u8; 4]> = row.iter().map(rgbe).collect();
+			if !(8..=0x7fff).contains(&row.len()) {
+				// Run-length encoding is only defined for these widths
+				writer.write_all(&pixels.concat())?;
+				continue;
+			}
+
+			// Marker of a run-length encoded scanline, followed by its width
+			writer.write_all(&[2, 2, (row.len() >> 8) as u8, (row.len() & 0xff) as u8])?;
+			// Each component is encoded separately
+			for component in 0..4 {
+				let values: Vec<u8> = pixels.iter().map(|pixel| pixel[component]).collect();
+				writer.write_all(&run_length_encode(&values))?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Converts the radiance to the shared exponent representation of Radiance HDR.
+fn rgbe(pixel: &Rgb) -> [u8; 4] {
+	let max = pixel.max_component();
+	if max < 1e-32 || max.is_nan() {
+		return [0, 0, 0, 0];
+	}
+	// Exponent such that max / 2^exponent is in 0.5..1.0
+	let exponent = (max.log2().floor() as i32 + 1).clamp(-128, 127);
+	let scale = 256.0 / 2f64.powi(exponent);
+	let mantissa = |component: f64| (component * scale).clamp(0.0, 255.0) as u8;
+	[mantissa(pixel.r), mantissa(pixel.g), mantissa(pixel.b), (exponent + 128) as u8]
+}
+
+/// Encodes the `values` as run-length encoded packets of Radiance HDR: Either a run of up to 127 equal values
+/// (`128 + count`, value), or up to 128 literal values (`count`, values).
+fn run_length_encode(values: &[u8]) -> Vec<u8> {
+	// Runs shorter than this are cheaper to store as literals
+	const MIN_RUN: usize = 4;
+
+	let run_length_at = |start: usize| {
+		values[start..].iter()
+			.take(127)
+			.take_while(|value| **value == values[start])
+			.count()
+	};
+
+	let mut encoded = Vec::new();
+	let mut start = 0;
+	while start < values.len() {
+		let run_length = run_length_at(start);
+		if run_length >= MIN_RUN {
+			encoded.extend_from_slice(&[128 + run_length as u8, values[start]]);
+			start += run_length;
+			continue;
+		}
+
+		// Collect literals until the next worthwhile run
+		let mut end = start + 1;
+		while end < values.len() && end - start < 128 && run_length_at(end) < MIN_RUN {
+			end += 1;
+		}
+		encoded.push((end - start) as u8);
+		encoded.extend_from_slice(&values[start..end]);
+		start = end;
+	}
+	encoded
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::Color;
+
+	use super::*;
+
+	#[test]
+	fn accumulate() {
+		let mut framebuffer = Framebuffer::new(2, 1);
+		let mut pass = Framebuffer::new(2, 1);
+		pass.rows[0][1] = Rgb::new(4.0, 2.0, 1.0);
+		framebuffer.accumulate(&pass, 0.5);
+		framebuffer.accumulate(&pass, 0.5);
+		assert_eq!(framebuffer.rows[0], vec![Rgb::BLACK, Rgb::new(4.0, 2.0, 1.0)]);
+	}
+
+	#[test]
+	fn to_image() {
+		let mut framebuffer = Framebuffer::new(2, 1);
+		framebuffer.rows[0][1] = Rgb::new(4.0, 0.25, 0.0);
+		let image = framebuffer.to_image(TransferFunction::Gamma(2.0));
+		assert_eq!((image.width(), image.height()), (2, 1));
+		assert_eq!(image.rows[0], vec![Color { r: 0, g: 0, b: 0 }, Color { r: 255, g: 128, b: 0 }]);
+	}
+
+	#[test]
+	fn png16() {
+		let mut framebuffer = Framebuffer::new(1, 1);
+		framebuffer.rows[0][0] = Rgb::new(1.0, 0.25, 0.0);
+		let mut png = Vec::new();
+		framebuffer.write_png16(&mut png, TransferFunction::Linear).unwrap();
+
+		let mut reader = png::Decoder::new(io::Cursor::new(png)).read_info().unwrap();
+		let mut data = vec![0; reader.output_buffer_size().unwrap()];
+		let info = reader.next_frame(&mut data).unwrap();
+		assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+		assert_eq!(data, [0xff, 0xff, 0x40, 0x00, 0x00, 0x00]);
+	}
+
+	#[test]
+	fn pfm() {
+		let mut framebuffer = Framebuffer::new(1, 2);
+		framebuffer.rows[1][0] = Rgb::new(8.0, 0.5, 0.0);
+		let mut pfm = Vec::new();
+		framebuffer.write_pfm(&mut pfm).unwrap();
+		assert!(pfm.starts_with(b"PF\n1 2\n-1.0\n"));
+		// The bottom row comes first
+		assert_eq!(&pfm[12..16], &8f32.to_le_bytes());
+		assert_eq!(pfm.len(), 12 + 2 * 3 * 4);
+	}
+
+	#[test]
+	fn rgbe() {
+		assert_eq!(super::rgbe(&Rgb::new(1.0, 0.5, 0.0)), [128, 64, 0, 129]);
+		assert_eq!(super::rgbe(&Rgb::new(12.0, 3.0, 0.75)), [192, 48, 12, 132]);
+		assert_eq!(super::rgbe(&Rgb::BLACK), [0, 0, 0, 0]);
+	}
+
+	#[test]
+	fn run_length_encode() {
+		let values = [1, 2, 3, 7, 7, 7, 7, 7, 4];
+		assert_eq!(super::run_length_encode(&values), vec![3, 1, 2, 3, 128 + 5, 7, 1, 4]);
+		let long_run = [9; 300];
+		assert_eq!(super::run_length_encode(&long_run), vec![128 + 127, 9, 128 + 127, 9, 128 + 46, 9]);
+	}
+
+	#[test]
+	fn radiance_hdr() {
+		let mut framebuffer = Framebuffer::new(8, 1);
+		framebuffer.rows[0][0] = Rgb::new(1.0, 0.5, 0.0);
+		let mut hdr = Vec::new();
+		framebuffer.write_radiance_hdr(&mut hdr).unwrap();
+		let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 8\n";
+		assert!(hdr.starts_with(header));
+		assert_eq!(&hdr[header.len()..], &[
+			2, 2, 0, 8,
+			1, 128, 128 + 7, 0,
+			1, 64, 128 + 7, 0,
+			128 + 8, 0,
+			1, 129, 128 + 7, 0,
+		]);
+	}
+
+	#[test]
+	fn exr_channels() {
+		let framebuffer = Framebuffer::new(2, 1);
+		let names = framebuffer.exr_channels("albedo", exr::SampleType::Half).map(|channel| channel.name);
+		assert_eq!(names, ["albedo.R", "albedo.G", "albedo.B"]);
+	}
+}
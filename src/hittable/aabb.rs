@@ -0,0 +1,128 @@
+//! Axis-aligned bounding boxes, used to quickly reject rays that can't hit an object.
+
+use crate::{Ray, Vec3};
+
+/// Axis-aligned bounding box spanned by its minimum and maximum corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+	/// Corner with the smallest coordinates.
+	pub min: Vec3,
+	/// Corner with the largest coordinates.
+	pub max: Vec3,
+}
+
+impl Aabb {
+	/// Creates the smallest box containing both points.
+	pub fn from_points(a: Vec3, b: Vec3) -> Self {
+		Self { min: a.min(b), max: a.max(b) }
+	}
+
+	/// Returns the smallest box containing both `self` and `other`.
+	pub fn surrounding(&self, other: &Aabb) -> Aabb {
+		Aabb { min: self.min.min(other.min), max: self.max.max(other.max) }
+	}
+
+	/// Returns the smallest box containing `self` and the `point`.
+	pub fn including(&self, point: Vec3) -> Aabb {
+		Aabb { min: self.min.min(point), max: self.max.max(point) }
+	}
+
+	/// Returns the center point of the box.
+	pub fn centroid(&self) -> Vec3 {
+		(self.min + self.max) * 0.5
+	}
+
+	/// Returns the size of the box along each axis.
+	pub fn extent(&self) -> Vec3 {
+		self.max - self.min
+	}
+
+	/// Returns the area of the six faces of the box.
+	pub fn surface_area(&self) -> f64 {
+		let extent = self.extent();
+		2.0 * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+	}
+
+	/// Returns the axis index (0 for x, 1 for y, 2 for z) along which the box is the longest.
+	pub fn longest_axis(&self) -> usize {
+		let extent = self.extent();
+		if extent.x >= extent.y && extent.x >= extent.z {
+			0
+		} else if extent.y >= extent.z {
+			1
+		} else {
+			2
+		}
+	}
+
+	/// Returns whether the [Ray] passes through the box somewhere between `t_min` and `t_max`.
+	///
+	/// Uses the slab method: The ray is clipped against the pair of planes on each axis, and the box is hit if
+	/// the remaining interval is not empty.
+	pub fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> bool {
+		self.clip(ray, t_min, t_max).is_some()
+	}
+
+	/// Returns the part of the interval from `t_min` to `t_max` in which the [Ray] is inside the box, if any.
+	pub fn clip(&self, ray: Ray, mut t_min: f64, mut t_max: f64) -> Option<(f64, f64)> {
+		for axis in 0..3 {
+			let inverse_direction = 1.0 / ray.direction[axis];
+			let mut t0 = (self.min[axis] - ray.origin[axis]) * inverse_direction;
+			let mut t1 = (self.max[axis] - ray.origin[axis]) * inverse_direction;
+			if inverse_direction < 0.0 {
+				std::mem::swap(&mut t0, &mut t1);
+			}
+			// f64::max/min ignore NaNs, which occur if the ray runs exactly inside a slab plane
+			t_min = f64::max(t0, t_min);
+			t_max = f64::min(t1, t_max);
+			if t_max < t_min {
+				return None;
+			}
+		}
+		Some((t_min, t_max))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn unit_box() -> Aabb {
+		Aabb::from_points(Vec3 { x: -1.0, y: -1.0, z: -1.0 }, Vec3 { x: 1.0, y: 1.0, z: 1.0 })
+	}
+
+	#[test]
+	fn hits() {
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
+		};
+		assert!(unit_box().hits(ray, 0.0, f64::INFINITY));
+		// Box is behind the allowed interval
+		assert!(!unit_box().hits(ray, 0.0, 3.0));
+		assert_eq!(unit_box().clip(ray, 0.0, f64::INFINITY), Some((4.0, 6.0)));
+		assert_eq!(unit_box().clip(ray, 5.0, 5.5), Some((5.0, 5.5)));
+	}
+
+	#[test]
+	fn misses() {
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			time: 0.0,
+		};
+		assert!(!unit_box().hits(ray, 0.0, f64::INFINITY));
+	}
+
+	#[test]
+	fn surrounding() {
+		let other = Aabb::from_points(Vec3 { x: 2.0, y: 0.0, z: 0.0 }, Vec3 { x: 3.0, y: 0.5, z: 0.5 });
+		let surrounding = unit_box().surrounding(&other);
+		assert_eq!(surrounding.min, Vec3 { x: -1.0, y: -1.0, z: -1.0 });
+		assert_eq!(surrounding.max, Vec3 { x: 3.0, y: 1.0, z: 1.0 });
+		assert_eq!(surrounding.longest_axis(), 0);
+		assert_eq!(unit_box().surface_area(), 24.0);
+	}
+}
@@ -0,0 +1,329 @@
+//! Bounding volume hierarchy (BVH) to find the nearest hit among many [Hittable]s in logarithmic instead of linear
+//! time.
+
+use std::fmt;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{Aabb, Hit, Hittable};
+
+/// Number of buckets the centroids are sorted into when searching for the best split.
+const BIN_COUNT: usize = 12;
+/// Leaves with at most this many hittables are not split further if splitting doesn't pay off.
+const MAX_LEAF_SIZE: usize = 4;
+/// Cost of testing a ray against the two child boxes of a node, relative to testing it against one hittable.
+const TRAVERSAL_COST: f64 = 1.0;
+
+/// Bounding volume hierarchy over a list of [Hittable]s, built with the surface area heuristic (SAH).
+///
+/// Returns the same hits as the list of hittables it was built from, but only tests a ray against the hittables
+/// whose bounding boxes it passes through.
+pub struct Bvh {
+	/// Flattened tree. The first child of an interior node directly follows it, the root is at index 0.
+	nodes: Vec<Node>,
+	/// Bounded hittables, ordered so that every leaf references a contiguous range.
+	hittables: Vec<Box<dyn Hittable>>,
+	/// Hittables without bounding box, which have to be tested against every ray.
+	unbounded: Vec<Box<dyn Hittable>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Node {
+	Leaf {
+		bounding_box: Aabb,
+		/// Index of the first hittable of this leaf in [Bvh::hittables].
+		start: usize,
+		count: usize,
+	},
+	Interior {
+		bounding_box: Aabb,
+		/// Index of the second child node. The first child is the node directly following this one.
+		second_child: usize,
+		/// Axis along which the children were split. The first child contains the lower coordinates.
+		axis: usize,
+	},
+}
+
+/// Bounded hittable during the construction of the tree.
+#[derive(Clone, Copy, Debug)]
+struct Primitive {
+	/// Index into the list of bounded hittables.
+	index: usize,
+	bounding_box: Aabb,
+	centroid: Vec3,
+}
+
+impl Bvh {
+	/// Builds the hierarchy over the given `hittables`.
+	pub fn new(hittables: Vec<Box<dyn Hittable>>) -> Self {
+		let mut bounded = Vec::new();
+		let mut primitives = Vec::new();
+		let mut unbounded = Vec::new();
+		for hittable in hittables {
+			match hittable.bounding_box() {
+				Some(bounding_box) => {
+					primitives.push(Primitive { index: bounded.len(), bounding_box, centroid: bounding_box.centroid() });
+					bounded.push(Some(hittable));
+				}
+				None => unbounded.push(hittable),
+			}
+		}
+
+		let mut nodes = Vec::new();
+		if !primitives.is_empty() {
+			Self::build(&mut nodes, &mut primitives, 0);
+		}
+
+		// Reorder the hittables the same way the construction reordered the primitives
+		let hittables = primitives.iter()
+			.map(|primitive| bounded[primitive.index].take().expect("Every hittable is referenced exactly once"))
+			.collect();
+
+		Self { nodes, hittables, unbounded }
+	}
+
+	/// Returns the number of hittables in the hierarchy.
+	pub fn len(&self) -> usize {
+		self.hittables.len() + self.unbounded.len()
+	}
+
+	/// Returns whether the hierarchy contains no hittables.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Recursively builds the subtree over `primitives` and appends it to `nodes`. `start` is the offset of
+	/// `primitives` in the list of all primitives.
+	fn build(nodes: &mut Vec<Node>, primitives: &mut [Primitive], start: usize) {
+		let bounding_box = primitives.iter()
+			.map(|primitive| primitive.bounding_box)
+			.reduce(|box1, box2| box1.surrounding(&box2))
+			.expect("Subtree contains at least one primitive");
+		let node_index = nodes.len();
+		nodes.push(Node::Leaf { bounding_box, start, count: primitives.len() });
+
+		let Some((axis, mid)) = Self::split(primitives, &bounding_box) else {
+			return;
+		};
+		Self::build(nodes, &mut primitives[..mid], start);
+		let second_child = nodes.len();
+		Self::build(nodes, &mut primitives[mid..], start + mid);
+		nodes[node_index] = Node::Interior { bounding_box, second_child, axis };
+	}
+
+	/// Partitions the `primitives` into two halves with minimal cost according to the surface area heuristic.
+	/// Returns the split axis and the index of the first primitive of the second half, or `None` if keeping all
+	/// primitives in a single leaf is cheaper.
+	fn split(primitives: &mut [Primitive], bounding_box: &Aabb) -> Option<(usize, usize)> {
+		let count = primitives.len();
+		if count <= 1 {
+			return None;
+		}
+
+		let centroid_box = primitives.iter()
+			.fold(Aabb::from_points(primitives[0].centroid, primitives[0].centroid), |centroid_box, primitive| {
+				centroid_box.including(primitive.centroid)
+			});
+		let axis = centroid_box.longest_axis();
+		let axis_min = centroid_box.min[axis];
+		let axis_extent = centroid_box.extent()[axis];
+
+		if axis_extent <= 0.0 {
+			// All centroids coincide, so no split can separate them. Split arbitrarily if there are too many.
+			return (count > MAX_LEAF_SIZE).then_some((axis, count / 2));
+		}
+
+		let bin_of = |primitive: &Primitive| {
+			let relative = (primitive.centroid[axis] - axis_min) / axis_extent;
+			usize::min((relative * BIN_COUNT as f64) as usize, BIN_COUNT - 1)
+		};
+
+		// Sort the primitives into the bins
+		let mut bin_boxes: [Option<Aabb>; BIN_COUNT] = [None; BIN_COUNT];
+		let mut bin_counts = [0usize; BIN_COUNT];
+		for primitive in primitives.iter() {
+			let bin = bin_of(primitive);
+			bin_counts[bin] += 1;
+			bin_boxes[bin] = Some(match bin_boxes[bin] {
+				Some(bin_box) => bin_box.surrounding(&primitive.bounding_box),
+				None => primitive.bounding_box,
+			});
+		}
+
+		// Evaluate the cost of splitting between every pair of neighbouring bins. The expected cost of a child is
+		// proportional to the probability of a ray hitting it (its surface area relative to the parent's) times the
+		// number of hittables in it.
+		let parent_area = bounding_box.surface_area();
+		let (split_bin, split_cost) = (1..BIN_COUNT)
+			.filter_map(|split_bin| {
+				let (left_box, left_count) = Self::merge_bins(&bin_boxes[..split_bin], &bin_counts[..split_bin])?;
+				let (right_box, right_count) = Self::merge_bins(&bin_boxes[split_bin..], &bin_counts[split_bin..])?;
+				let cost = TRAVERSAL_COST
+					+ (left_box.surface_area() * left_count as f64 + right_box.surface_area() * right_count as f64)
+					/ parent_area;
+				Some((split_bin, cost))
+			})
+			.min_by(|(_, cost1), (_, cost2)| cost1.total_cmp(cost2))?;
+
+		let leaf_cost = count as f64;
+		if count <= MAX_LEAF_SIZE && leaf_cost <= split_cost {
+			return None;
+		}
+
+		// Move all primitives left of the split to the front
+		let mut mid = 0;
+		for i in 0..count {
+			if bin_of(&primitives[i]) < split_bin {
+				primitives.swap(i, mid);
+				mid += 1;
+			}
+		}
+		Some((axis, mid))
+	}
+
+	/// Returns the box surrounding all bins and the total number of primitives in them, or `None` if the bins are
+	/// empty.
+	fn merge_bins(boxes: &[Option<Aabb>], counts: &[usize]) -> Option<(Aabb, usize)> {
+		let merged_box = boxes.iter()
+			.flatten()
+			.copied()
+			.reduce(|box1, box2| box1.surrounding(&box2))?;
+		Some((merged_box, counts.iter().sum()))
+	}
+}
+
+impl fmt::Debug for Bvh {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Bvh")
+			.field("node_count", &self.nodes.len())
+			.field("hittable_count", &self.hittables.len())
+			.field("unbounded_count", &self.unbounded.len())
+			.finish()
+	}
+}
+
+impl Hittable for Bvh {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let mut closest = self.unbounded.as_slice().hits(ray, t_min, t_max);
+		// Only hits closer than the closest one found so far are interesting
+		let mut t_max = closest.as_ref().map_or(t_max, |hit| hit.t);
+
+		if self.nodes.is_empty() {
+			return closest;
+		}
+
+		let mut stack = Vec::with_capacity(32);
+		stack.push(0);
+		while let Some(node_index) = stack.pop() {
+			match self.nodes[node_index] {
+				Node::Leaf { bounding_box, start, count } => {
+					if !bounding_box.hits(ray, t_min, t_max) {
+						continue;
+					}
+					for hittable in &self.hittables[start..start + count] {
+						if let Some(hit) = hittable.hits(ray, t_min, t_max) {
+							t_max = hit.t;
+							closest = Some(hit);
+						}
+					}
+				}
+				Node::Interior { bounding_box, second_child, axis } => {
+					if !bounding_box.hits(ray, t_min, t_max) {
+						continue;
+					}
+					// Visit the child nearer to the ray origin first, so that the hits found in it can shrink
+					// `t_max` and the farther child may be skipped.
+					let first_child = node_index + 1;
+					if ray.direction[axis] < 0.0 {
+						stack.push(first_child);
+						stack.push(second_child);
+					} else {
+						stack.push(second_child);
+						stack.push(first_child);
+					}
+				}
+			}
+		}
+		closest
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		if !self.unbounded.is_empty() {
+			return None;
+		}
+		match self.nodes.first()? {
+			Node::Leaf { bounding_box, .. } | Node::Interior { bounding_box, .. } => Some(*bounding_box),
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use rand::{Rng, SeedableRng};
+	use rand::rngs::StdRng;
+
+	use crate::{hittable::Sphere, material, Vec3};
+
+	use super::*;
+
+	fn random_vec(rng: &mut StdRng, range: std::ops::Range<f64>) -> Vec3 {
+		Vec3 {
+			x: rng.gen_range(range.clone()),
+			y: rng.gen_range(range.clone()),
+			z: rng.gen_range(range),
+		}
+	}
+
+	fn random_spheres(rng: &mut StdRng, count: usize) -> Vec<Box<dyn Hittable>> {
+		let material: Arc<dyn material::Material> = Arc::new(material::Lambertian::default());
+		(0..count)
+			.map(|_| Box::new(Sphere {
+				center: random_vec(rng, -10.0..10.0),
+				radius: rng.gen_range(0.05..1.0),
+				material: Arc::clone(&material),
+			}) as Box<dyn Hittable>)
+			.collect()
+	}
+
+	#[test]
+	fn same_hits_as_list() {
+		let mut rng = StdRng::seed_from_u64(42);
+		let list = random_spheres(&mut rng, 500);
+		let bvh = Bvh::new(random_spheres(&mut StdRng::seed_from_u64(42), 500));
+		assert_eq!(bvh.len(), 500);
+
+		for _ in 0..2000 {
+			let ray = Ray {
+				origin: random_vec(&mut rng, -15.0..15.0),
+				direction: random_vec(&mut rng, -1.0..1.0),
+				time: 0.0,
+			};
+			let list_hit = list.as_slice().hits(ray, 0.0001, f64::INFINITY);
+			let bvh_hit = bvh.hits(ray, 0.0001, f64::INFINITY);
+			assert_eq!(list_hit.map(|hit| (hit.t, hit.point)), bvh_hit.map(|hit| (hit.t, hit.point)));
+		}
+	}
+
+	#[test]
+	fn bounding_box() {
+		let mut rng = StdRng::seed_from_u64(7);
+		let list = random_spheres(&mut rng, 50);
+		let list_box = list.as_slice().bounding_box();
+		assert_eq!(Bvh::new(list).bounding_box(), list_box);
+	}
+
+	#[test]
+	fn empty() {
+		let bvh = Bvh::new(Vec::new());
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
+		};
+		assert!(bvh.is_empty());
+		assert!(bvh.hits(ray, 0.0, f64::INFINITY).is_none());
+		assert!(bvh.bounding_box().is_none());
+	}
+}
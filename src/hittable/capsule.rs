@@ -0,0 +1,142 @@
+//! A capsule is a cylinder with hemispherical ends, i.e. all points within a distance of a line segment.
+
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{Aabb, Hit, Hittable};
+use crate::hittable::frame::{self, AxisFrame, NearestHit};
+use crate::material::Material;
+
+/// All points within `radius` of the line segment from `start` to `end`.
+///
+/// The texture coordinate `u` is the angle around the axis and `v` runs from 0.0 at the tip behind `start` to 1.0
+/// at the tip behind `end`.
+#[derive(Clone, Debug)]
+pub struct Capsule {
+	pub start: Vec3,
+	pub end: Vec3,
+	pub radius: f64,
+	pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Capsule {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let axis = self.end - self.start;
+		let height = axis.length();
+		let frame = AxisFrame::new(self.start, axis);
+		let local = frame.ray_to_local(ray);
+		let (origin, direction) = (local.origin, local.direction);
+		let radius_squared = self.radius * self.radius;
+		let mut nearest = NearestHit::new(t_min, t_max);
+		let mut consider = |t: f64| {
+			let point = local.at(t);
+			nearest.consider(t, || {
+				// The normal points away from the nearest point on the axis
+				let axis_point = Vec3 { x: 0.0, y: point.y.clamp(0.0, height), z: 0.0 };
+				let normal = (point - axis_point) / self.radius;
+				let v = (point.y + self.radius) / (height + 2.0 * self.radius);
+				(normal, [frame::angle_around_axis(point), v])
+			});
+		};
+
+		// Tube between y = 0 and y = height
+		let a = direction.x * direction.x + direction.z * direction.z;
+		let half_b = origin.x * direction.x + origin.z * direction.z;
+		let c = origin.x * origin.x + origin.z * origin.z - radius_squared;
+		let discriminant = half_b * half_b - a * c;
+		if a > 1e-12 && discriminant >= 0.0 {
+			for t in [(-half_b - discriminant.sqrt()) / a, (-half_b + discriminant.sqrt()) / a] {
+				if (0.0..=height).contains(&local.at(t).y) {
+					consider(t);
+				}
+			}
+		}
+
+		// Hemispheres beyond the ends of the tube
+		let a = direction.squared_length();
+		for (center_y, outside_end) in [(0.0, -1.0), (height, 1.0)] {
+			let offset = origin - Vec3 { x: 0.0, y: center_y, z: 0.0 };
+			let half_b = offset.dot(direction);
+			let c = offset.squared_length() - radius_squared;
+			let discriminant = half_b * half_b - a * c;
+			if discriminant >= 0.0 {
+				for t in [(-half_b - discriminant.sqrt()) / a, (-half_b + discriminant.sqrt()) / a] {
+					if (local.at(t).y - center_y) * outside_end >= 0.0 {
+						consider(t);
+					}
+				}
+			}
+		}
+
+		nearest.hit(ray, &frame, &self.material)
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		let radius = Vec3 { x: self.radius, y: self.radius, z: self.radius };
+		Some(Aabb::from_points(self.start - radius, self.start + radius).surrounding(
+			&Aabb::from_points(self.end - radius, self.end + radius),
+		))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	/// Capsule along the z axis from z = 0.0 to z = 2.0 with radius 0.5.
+	fn capsule() -> Capsule {
+		Capsule {
+			start: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			end: Vec3 { x: 0.0, y: 0.0, z: 2.0 },
+			radius: 0.5,
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	#[test]
+	fn hits() {
+		// Along the axis into a hemisphere
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: 5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let hit = capsule().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.5).abs() < 1e-9);
+		hit.normal.assert_near(Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+		assert!((hit.uv[1] - 1.0).abs() < 1e-9);
+
+		// Onto the tube from the side
+		let ray = Ray { origin: Vec3 { x: 3.0, y: 0.0, z: 1.0 }, direction: Vec3 { x: -1.0, y: 0.0, z: 0.0 }, ..ray };
+		let hit = capsule().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.5).abs() < 1e-9);
+		hit.normal.assert_near(Vec3 { x: 1.0, y: 0.0, z: 0.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		assert!((hit.uv[1] - 0.5).abs() < 1e-9);
+
+		// From the inside, out through the hemisphere at the start
+		let ray = Ray { origin: Vec3 { x: 0.0, y: 0.0, z: 1.0 }, direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 }, ..ray };
+		let hit = capsule().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 1.5).abs() < 1e-9);
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+		hit.normal.assert_near(Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+	}
+
+	#[test]
+	fn misses() {
+		// Just beside the hemisphere, where the tube would be hit if it continued
+		let ray = Ray {
+			origin: Vec3 { x: 0.45, y: 5.0, z: 2.4 },
+			direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+			time: 0.0,
+		};
+		assert!(capsule().hits(ray, 0.0, f64::INFINITY).is_none());
+		let bounding_box = capsule().bounding_box().unwrap();
+		bounding_box.min.assert_near(Vec3 { x: -0.5, y: -0.5, z: -0.5 });
+		bounding_box.max.assert_near(Vec3 { x: 0.5, y: 0.5, z: 2.5 });
+	}
+}
@@ -0,0 +1,145 @@
+//! A cone narrows from a circular base to a tip, like a funnel or the tip of a bolt.
+
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{disk, Aabb, Hit, Hittable};
+use crate::hittable::frame::{self, AxisFrame, NearestHit};
+use crate::material::Material;
+
+/// A finite cone with a base of `radius` around `base`, narrowing to a point at `apex`, optionally closed by a disk
+/// at the base.
+///
+/// On the mantle, the texture coordinate `u` is the angle around the axis and `v` runs from 0.0 at the base to 1.0
+/// at the apex. On the base, `u` is the angle and `v` the distance from the axis relative to the radius.
+#[derive(Clone, Debug)]
+pub struct Cone {
+	pub base: Vec3,
+	pub apex: Vec3,
+	pub radius: f64,
+	/// Whether the base is closed.
+	pub capped: bool,
+	pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Cone {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let axis = self.apex - self.base;
+		let height = axis.length();
+		let frame = AxisFrame::new(self.base, axis);
+		let local = frame.ray_to_local(ray);
+		let (origin, direction) = (local.origin, local.direction);
+		let mut nearest = NearestHit::new(t_min, t_max);
+
+		// Mantle: x² + z² = (k (height - y))² between y = 0 and y = height, with the slope k = radius / height
+		let k2 = (self.radius / height).powi(2);
+		let h = height - origin.y;
+		let a = direction.x * direction.x + direction.z * direction.z - k2 * direction.y * direction.y;
+		let half_b = origin.x * direction.x + origin.z * direction.z + k2 * h * direction.y;
+		let c = origin.x * origin.x + origin.z * origin.z - k2 * h * h;
+		// Missing roots are NaN, which is never in range
+		let roots = if a.abs() > 1e-12 {
+			let discriminant_root = (half_b * half_b - a * c).sqrt();
+			[(-half_b - discriminant_root) / a, (-half_b + discriminant_root) / a]
+		} else {
+			// The ray is parallel to the mantle and crosses it only once
+			[-c / (2.0 * half_b), f64::NAN]
+		};
+		for t in roots {
+			let point = local.at(t);
+			if (0.0..=height).contains(&point.y) {
+				nearest.consider(t, || {
+					// Gradient of the cone's equation, pointing straight along the axis at the apex
+					let normal = Vec3 { x: point.x, y: k2 * (height - point.y), z: point.z };
+					let normal = if normal.is_near_zero() {
+						Vec3 { x: 0.0, y: 1.0, z: 0.0 }
+					} else {
+						normal.unit_vector()
+					};
+					(normal, [frame::angle_around_axis(point), point.y / height])
+				});
+			}
+		}
+
+		if self.capped && direction.y.abs() > 1e-12 {
+			let t = -origin.y / direction.y;
+			let point = local.at(t);
+			let distance = (point.x * point.x + point.z * point.z).sqrt();
+			if distance <= self.radius {
+				nearest.consider(t, || {
+					(Vec3 { x: 0.0, y: -1.0, z: 0.0 }, [frame::angle_around_axis(point), distance / self.radius])
+				});
+			}
+		}
+
+		nearest.hit(ray, &frame, &self.material)
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		let extent = disk::extent((self.apex - self.base).unit_vector(), self.radius);
+		Some(Aabb::from_points(self.base - extent, self.base + extent).surrounding(&Aabb::from_points(self.apex, self.apex)))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	/// Cone standing on the xz plane with a 45° slope.
+	fn cone() -> Cone {
+		Cone {
+			base: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			apex: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			radius: 1.0,
+			capped: true,
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	#[test]
+	fn mantle() {
+		let ray = Ray {
+			origin: Vec3 { x: 0.5, y: 5.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = cone().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 4.5).abs() < 1e-9);
+		let expected_normal = Vec3 { x: 1.0, y: 1.0, z: 0.0 }.unit_vector();
+		assert!((hit.normal - expected_normal).length() < 1e-9);
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		assert!((hit.uv[1] - 0.5).abs() < 1e-9);
+
+		// Horizontally through the tip region, missing the mantle above the apex
+		let ray = Ray { origin: Vec3 { x: -5.0, y: 1.5, z: 0.0 }, direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 }, ..ray };
+		assert!(cone().hits(ray, 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn base() {
+		let ray = Ray {
+			origin: Vec3 { x: 0.5, y: -2.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = cone().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.0).abs() < 1e-9);
+		assert!((hit.normal - Vec3 { x: 0.0, y: -1.0, z: 0.0 }).length() < 1e-9);
+
+		let open = Cone { capped: false, ..cone() };
+		let hit = open.hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.5).abs() < 1e-9);
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+	}
+
+	#[test]
+	fn bounding_box() {
+		let bounding_box = cone().bounding_box().unwrap();
+		assert!((bounding_box.min - Vec3 { x: -1.0, y: 0.0, z: -1.0 }).length() < 1e-9);
+		assert!((bounding_box.max - Vec3 { x: 1.0, y: 1.0, z: 1.0 }).length() < 1e-9);
+	}
+}
@@ -0,0 +1,121 @@
+//! Participating media like smoke, fog or murky water, which scatter light inside their volume instead of at their
+//! surface.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{random, Ray};
+use crate::hittable::{Aabb, Hit, Hittable, IntersectionSide};
+use crate::material::Material;
+
+/// A volume of constant `density` inside the `boundary`, which scatters rays at random distances with its
+/// `phase_function`, e.g. [Isotropic](crate::material::Isotropic).
+///
+/// The boundary has to be convex, since a ray is assumed to be inside between the first two intersections with it.
+pub struct ConstantMedium {
+	pub boundary: Box<dyn Hittable>,
+	/// Probability per unit length of a ray being scattered.
+	pub density: f64,
+	pub phase_function: Arc<dyn Material>,
+}
+
+impl Hittable for ConstantMedium {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		// The ray may start inside the medium, so the boundary is intersected along the whole line
+		let entry = self.boundary.hits(ray, f64::NEG_INFINITY, f64::INFINITY)?;
+		let exit = self.boundary.hits(ray, entry.t + 0.0001, f64::INFINITY)?;
+		let entry_t = entry.t.max(t_min);
+		let exit_t = exit.t.min(t_max);
+		if entry_t >= exit_t {
+			return None;
+		}
+
+		// The distance to the scattering is exponentially distributed
+		let speed = ray.direction.length();
+		let distance_inside = (exit_t - entry_t) * speed;
+		let scatter_distance = -(1.0 - random::gen_range(0.0..1.0)).ln() / self.density;
+		if scatter_distance > distance_inside {
+			return None;
+		}
+
+		let t = entry_t + scatter_distance / speed;
+		Some(Hit {
+			point: ray.at(t),
+			// Scattering inside a volume has no surface normal. This one faces the ray like a surface hit from outside.
+			normal: -ray.direction / speed,
+			t,
+			intersection_side: IntersectionSide::Outside,
+			uv: [0.0, 0.0],
+			material: Arc::clone(&self.phase_function),
+		})
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		self.boundary.bounding_box()
+	}
+}
+
+impl fmt::Debug for ConstantMedium {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ConstantMedium")
+			.field("density", &self.density)
+			.field("phase_function", &self.phase_function)
+			.finish_non_exhaustive()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::{Rgb, Vec3};
+	use crate::hittable::Sphere;
+	use crate::material::Isotropic;
+
+	use super::*;
+
+	fn fog(density: f64) -> ConstantMedium {
+		ConstantMedium {
+			boundary: Box::new(Sphere {
+				center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+				radius: 1.0,
+				material: Arc::new(Isotropic { albedo: Arc::new(Rgb::WHITE) }),
+			}),
+			density,
+			phase_function: Arc::new(Isotropic { albedo: Arc::new(Rgb::WHITE) }),
+		}
+	}
+
+	#[test]
+	fn transmittance() {
+		random::seed(3);
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 2.0 },
+			time: 0.0,
+		};
+		// The fraction of rays passing through the diameter of 2.0 is e^(-density * 2.0)
+		let medium = fog(0.5);
+		let count = 10_000;
+		let hits: Vec<Hit> = (0..count).filter_map(|_| medium.hits(ray, 0.0, f64::INFINITY)).collect();
+		let transmitted = 1.0 - hits.len() as f64 / count as f64;
+		assert!((transmitted - (-1.0f64).exp()).abs() < 0.02, "{}", transmitted);
+		assert!(hits.iter().all(|hit| (1.5..=3.5).contains(&hit.t) && hit.point.x == 0.0));
+	}
+
+	#[test]
+	fn inside_and_range() {
+		random::seed(3);
+		let medium = fog(1000.0);
+		// Starting inside, the ray scatters almost immediately
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = medium.hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!(hit.t < 0.1);
+		// Nothing is hit if the medium is outside the range
+		assert!(medium.hits(ray, 2.0, f64::INFINITY).is_none());
+		assert!(medium.hits(Ray { origin: Vec3 { x: 0.0, y: 3.0, z: 0.0 }, ..ray }, 0.0, f64::INFINITY).is_none());
+	}
+}
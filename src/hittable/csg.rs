@@ -0,0 +1,295 @@
+//! Constructive solid geometry combines closed objects into new ones, e.g. lenses, hollow shells or cut-away
+//! parts.
+
+use std::fmt;
+
+use crate::Ray;
+use crate::hittable::{Aabb, Hit, Hittable, IntersectionSide};
+
+/// How a [Csg] combines the insides of its objects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+	/// Inside either object.
+	Union,
+	/// Inside both objects.
+	Intersection,
+	/// Inside the left object, but not the right one.
+	Difference,
+}
+
+impl Operation {
+	fn contains(&self, in_left: bool, in_right: bool) -> bool {
+		match self {
+			Operation::Union => in_left || in_right,
+			Operation::Intersection => in_left && in_right,
+			Operation::Difference => in_left && !in_right,
+		}
+	}
+}
+
+/// Part of a ray inside a closed object, from where the ray enters to where it leaves the object.
+#[derive(Clone, Debug)]
+pub struct Interval {
+	pub entry: Hit,
+	pub exit: Hit,
+}
+
+/// Combination of two closed objects. Surfaces keep the material of the object they belong to, so the faces cut by
+/// a [Difference](Operation::Difference) have the material of the right object.
+pub struct Csg {
+	pub operation: Operation,
+	pub left: Box<dyn Hittable>,
+	pub right: Box<dyn Hittable>,
+}
+
+impl Hittable for Csg {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		self.intervals(ray, t_min, t_max)
+			.into_iter()
+			.flat_map(|interval| [interval.entry, interval.exit])
+			.find(|hit| (t_min..t_max).contains(&hit.t))
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		match self.operation {
+			Operation::Union => Some(self.left.bounding_box()?.surrounding(&self.right.bounding_box()?)),
+			Operation::Intersection => match (self.left.bounding_box(), self.right.bounding_box()) {
+				(Some(left), Some(right)) => {
+					let (min, max) = (left.min.max(right.min), left.max.min(right.max));
+					// The boxes may not overlap, in which case the object is empty and any box will do
+					Some(Aabb { min, max: min.max(max) })
+				}
+				(left, right) => left.or(right),
+			},
+			Operation::Difference => self.left.bounding_box(),
+		}
+	}
+
+	fn intervals(&self, ray: Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+		if self.bounding_box().is_some_and(|bounds| !bounds.hits(ray, t_min, t_max)) {
+			return Vec::new();
+		}
+
+		// The boundaries of both objects' intervals, ordered by distance, each toggling whether the ray is inside
+		// that object. Intervals outside the range don't change whether the ray is inside within it.
+		let mut boundaries: Vec<(Hit, bool)> = [(&self.left, true), (&self.right, false)]
+			.into_iter()
+			.flat_map(|(object, is_left)| {
+				object.intervals(ray, t_min, t_max)
+					.into_iter()
+					.flat_map(move |interval| [(interval.entry, is_left), (interval.exit, is_left)])
+			})
+			.collect();
+		boundaries.sort_by(|(hit1, _), (hit2, _)| hit1.t.total_cmp(&hit2.t));
+
+		let (mut in_left, mut in_right) = (false, false);
+		let mut entry = None;
+		let mut intervals = Vec::new();
+		for (hit, is_left) in boundaries {
+			if is_left {
+				in_left = !in_left;
+			} else {
+				in_right = !in_right;
+			}
+			// The normals of hits already face the ray, only the side has to match the combined object
+			let inside = self.operation.contains(in_left, in_right);
+			match entry.take() {
+				None if inside => entry = Some(Hit { intersection_side: IntersectionSide::Outside, ..hit }),
+				Some(entry_hit) if !inside => intervals.push(Interval {
+					entry: entry_hit,
+					exit: Hit { intersection_side: IntersectionSide::Inside, ..hit },
+				}),
+				unchanged => entry = unchanged,
+			}
+		}
+		// A difference or intersection may leave parts outside the range
+		intervals.retain(|interval| interval.exit.t >= t_min && interval.entry.t <= t_max);
+		intervals
+	}
+}
+
+impl fmt::Debug for Csg {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Csg")
+			.field("operation", &self.operation)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Returns the intervals inside `object` overlapping the range from `t_min` to `t_max` by collecting its hits along
+/// the ray. A hit from the outside enters the object and a hit from the inside leaves it. Hits that don't fit in,
+/// e.g. of open surfaces, are skipped.
+pub(crate) fn intervals_from_hits<H: Hittable + ?Sized>(object: &H, ray: Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+	// Limits the number of hits in case an object keeps being hit at the same distance
+	const MAX_HITS: usize = 1000;
+
+	// An interval overlapping the range may begin before it, but not before the ray enters the bounding box
+	let mut t = match object.bounding_box() {
+		Some(bounds) => match bounds.clip(ray, f64::NEG_INFINITY, t_max) {
+			Some((entry, exit)) if exit >= t_min => entry - 0.0001,
+			_ => return Vec::new(),
+		},
+		None => f64::NEG_INFINITY,
+	};
+	let mut intervals = Vec::new();
+	let mut entry = None;
+	for _ in 0..MAX_HITS {
+		let Some(hit) = object.hits(ray, t, f64::INFINITY) else {
+			break;
+		};
+		t = hit.t + 0.0001;
+		match (hit.intersection_side, entry.take()) {
+			// Intervals beginning after the range don't overlap it
+			(IntersectionSide::Outside, _) if hit.t > t_max => break,
+			(IntersectionSide::Outside, _) => entry = Some(hit),
+			// Neither do those ending before it
+			(IntersectionSide::Inside, Some(entry)) if hit.t >= t_min => intervals.push(Interval { entry, exit: hit }),
+			(IntersectionSide::Inside, _) => {}
+		}
+	}
+	intervals
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{self, AtomicUsize};
+
+	use crate::Vec3;
+	use crate::hittable::{Bvh, Cuboid, Sphere};
+	use crate::material;
+
+	use super::*;
+
+	fn sphere(x: f64, radius: f64) -> Box<dyn Hittable> {
+		Box::new(Sphere {
+			center: Vec3 { x, y: 0.0, z: 0.0 },
+			radius,
+			material: Arc::new(material::Lambertian::default()),
+		})
+	}
+
+	fn csg(operation: Operation, left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Csg {
+		Csg { operation, left, right }
+	}
+
+	/// Ray along the x axis from x = -10.0.
+	fn ray() -> Ray {
+		Ray {
+			origin: Vec3 { x: -10.0, y: 0.0, z: 0.0 },
+			direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+			time: 0.0,
+		}
+	}
+
+	/// Returns the x coordinates of the entries and exits of the intervals along [ray].
+	fn boundaries(object: &dyn Hittable) -> Vec<(f64, f64)> {
+		object.intervals(ray(), f64::NEG_INFINITY, f64::INFINITY)
+			.iter()
+			.map(|interval| {
+				assert_eq!(interval.entry.intersection_side, IntersectionSide::Outside);
+				assert_eq!(interval.exit.intersection_side, IntersectionSide::Inside);
+				// Normals face the ray
+				assert!(interval.entry.normal.x < 0.0 && interval.exit.normal.x < 0.0);
+				(interval.entry.point.x, interval.exit.point.x)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn intervals_from_hits() {
+		assert_eq!(boundaries(sphere(0.0, 1.0).as_ref()), [(-1.0, 1.0)]);
+		let cuboid = Cuboid {
+			min: Vec3 { x: 2.0, y: -1.0, z: -1.0 },
+			max: Vec3 { x: 3.0, y: 1.0, z: 1.0 },
+			material: Arc::new(material::Lambertian::default()),
+		};
+		assert_eq!(boundaries(&cuboid), [(2.0, 3.0)]);
+		// The whole range counts, also behind the ray's origin
+		let ray = Ray { origin: Vec3 { x: 5.0, y: 0.0, z: 0.0 }, ..ray() };
+		assert_eq!(cuboid.intervals(ray, f64::NEG_INFINITY, f64::INFINITY).len(), 1);
+	}
+
+	#[test]
+	fn range() {
+		let union = csg(Operation::Union, sphere(0.0, 2.0), sphere(3.0, 2.0));
+		// Intervals overlapping the range are kept whole
+		let intervals = union.intervals(ray(), 12.0, 13.0);
+		assert_eq!(intervals.len(), 1);
+		assert_eq!((intervals[0].entry.t, intervals[0].exit.t), (8.0, 15.0));
+		assert!(union.intervals(ray(), 15.5, f64::INFINITY).is_empty());
+		assert!(union.hits(ray(), 0.0, 7.5).is_none());
+		// Parts of a difference outside the range are dropped
+		let difference = csg(Operation::Difference, sphere(0.0, 2.0), sphere(0.0, 1.0));
+		assert_eq!(difference.intervals(ray(), 10.5, 20.0).len(), 1);
+	}
+
+	#[test]
+	fn stops_after_range() {
+		/// Counts the intersection tests of the object.
+		struct Counted<H> {
+			object: H,
+			calls: Arc<AtomicUsize>,
+		}
+
+		impl<H: Hittable> Hittable for Counted<H> {
+			fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+				self.calls.fetch_add(1, atomic::Ordering::Relaxed);
+				self.object.hits(ray, t_min, t_max)
+			}
+
+			fn bounding_box(&self) -> Option<Aabb> {
+				self.object.bounding_box()
+			}
+		}
+
+		// A row of 100 spheres along the ray, of which only the first is in range
+		let calls = Arc::new(AtomicUsize::new(0));
+		let row = Counted {
+			object: Bvh::new((0..100).map(|index| sphere(index as f64 * 3.0, 1.0)).collect()),
+			calls: Arc::clone(&calls),
+		};
+		let difference = csg(Operation::Difference, Box::new(row), sphere(0.0, 0.5));
+		assert_eq!(difference.hits(ray(), 0.0, 10.0).unwrap().point.x, -1.0);
+		// The entry and exit of the first sphere and the entry of the second one
+		assert_eq!(calls.load(atomic::Ordering::Relaxed), 3);
+	}
+
+	#[test]
+	fn operations() {
+		let union = csg(Operation::Union, sphere(0.0, 2.0), sphere(3.0, 2.0));
+		assert_eq!(boundaries(&union), [(-2.0, 5.0)]);
+		let intersection = csg(Operation::Intersection, sphere(0.0, 2.0), sphere(3.0, 2.0));
+		assert_eq!(boundaries(&intersection), [(1.0, 2.0)]);
+		let difference = csg(Operation::Difference, sphere(0.0, 2.0), sphere(3.0, 2.0));
+		assert_eq!(boundaries(&difference), [(-2.0, 1.0)]);
+		let apart = csg(Operation::Intersection, sphere(0.0, 1.0), sphere(5.0, 1.0));
+		assert!(boundaries(&apart).is_empty());
+	}
+
+	#[test]
+	fn hollow_shell() {
+		let shell = csg(Operation::Difference, sphere(0.0, 2.0), sphere(0.0, 1.0));
+		assert_eq!(boundaries(&shell), [(-2.0, -1.0), (1.0, 2.0)]);
+		// From the center, the ray enters the shell at the inner sphere
+		let ray = Ray { origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 }, ..ray() };
+		let hit = shell.hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!((hit.t, hit.intersection_side), (1.0, IntersectionSide::Outside));
+		assert_eq!(hit.normal, Vec3 { x: -1.0, y: 0.0, z: 0.0 });
+		// Inside the shell, the next hit leaves it
+		let hit = shell.hits(ray, 1.5, f64::INFINITY).unwrap();
+		assert_eq!((hit.t, hit.intersection_side), (2.0, IntersectionSide::Inside));
+	}
+
+	#[test]
+	fn nested() {
+		// A lens with a hole drilled through its center
+		let lens = csg(Operation::Intersection, sphere(-1.5, 2.0), sphere(1.5, 2.0));
+		let drilled = csg(Operation::Difference, Box::new(lens), sphere(0.0, 0.25));
+		assert_eq!(boundaries(&drilled), [(-0.5, -0.25), (0.25, 0.5)]);
+		let bounding_box = drilled.bounding_box().unwrap();
+		assert_eq!((bounding_box.min.x, bounding_box.max.x), (-0.5, 0.5));
+		assert_eq!((bounding_box.min.y, bounding_box.max.y), (-2.0, 2.0));
+	}
+}
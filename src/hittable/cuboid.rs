@@ -0,0 +1,121 @@
+//! A cuboid is a box with six rectangular faces, e.g. for furniture or the blocks in a Cornell box.
+
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{plane, Aabb, Hit, Hittable};
+use crate::material::Material;
+
+/// An axis-aligned box spanned by its minimum and maximum corner. Rotated boxes are [Instance](super::Instance)s of
+/// cuboids.
+///
+/// The texture coordinates run from 0.0 to 1.0 across each face.
+#[derive(Clone, Debug)]
+pub struct Cuboid {
+	/// Corner with the smallest coordinates.
+	pub min: Vec3,
+	/// Corner with the largest coordinates.
+	pub max: Vec3,
+	pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Cuboid {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		// Slab method, remembering the axes of the faces through which the ray enters and leaves the box
+		let (mut t_enter, mut enter_axis) = (f64::NEG_INFINITY, 0);
+		let (mut t_leave, mut leave_axis) = (f64::INFINITY, 0);
+		for axis in 0..3 {
+			let inverse_direction = 1.0 / ray.direction[axis];
+			let mut t0 = (self.min[axis] - ray.origin[axis]) * inverse_direction;
+			let mut t1 = (self.max[axis] - ray.origin[axis]) * inverse_direction;
+			if inverse_direction < 0.0 {
+				std::mem::swap(&mut t0, &mut t1);
+			}
+			if t0 > t_enter {
+				(t_enter, enter_axis) = (t0, axis);
+			}
+			if t1 < t_leave {
+				(t_leave, leave_axis) = (t1, axis);
+			}
+		}
+		if t_leave < t_enter {
+			return None;
+		}
+
+		// The outward normal of the entry face points against the ray, that of the exit face along it
+		let (t, axis, sign) = if (t_min..t_max).contains(&t_enter) {
+			(t_enter, enter_axis, -ray.direction[enter_axis].signum())
+		} else if (t_min..t_max).contains(&t_leave) {
+			(t_leave, leave_axis, ray.direction[leave_axis].signum())
+		} else {
+			return None;
+		};
+		let outward_normal = match axis {
+			0 => Vec3 { x: sign, y: 0.0, z: 0.0 },
+			1 => Vec3 { x: 0.0, y: sign, z: 0.0 },
+			_ => Vec3 { x: 0.0, y: 0.0, z: sign },
+		};
+
+		let point = ray.at(t);
+		let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+		let coordinate = |axis: usize| (point[axis] - self.min[axis]) / (self.max[axis] - self.min[axis]);
+		Some(plane::hit(ray, t, [coordinate(u_axis), coordinate(v_axis)], outward_normal, &self.material))
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		Some(Aabb::from_points(self.min, self.max))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	fn cuboid() -> Cuboid {
+		Cuboid {
+			min: Vec3 { x: -1.0, y: 0.0, z: -4.0 },
+			max: Vec3 { x: 1.0, y: 2.0, z: -2.0 },
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	#[test]
+	fn hits() {
+		let ray = Ray {
+			origin: Vec3 { x: 0.5, y: 1.5, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let hit = cuboid().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 2.0);
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		assert_eq!(hit.uv, [0.75, 0.75]);
+
+		// From the side and from the inside
+		let ray = Ray { origin: Vec3 { x: 5.0, y: 1.0, z: -3.0 }, direction: Vec3 { x: -1.0, y: 0.0, z: 0.0 }, ..ray };
+		assert_eq!(cuboid().hits(ray, 0.0, f64::INFINITY).unwrap().normal, Vec3 { x: 1.0, y: 0.0, z: 0.0 });
+		let ray = Ray { origin: Vec3 { x: 0.0, y: 1.0, z: -3.0 }, direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 }, ..ray };
+		let hit = cuboid().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 1.0);
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+	}
+
+	#[test]
+	fn misses() {
+		let ray = Ray {
+			origin: Vec3 { x: 1.5, y: 1.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		assert!(cuboid().hits(ray, 0.0, f64::INFINITY).is_none());
+		// Behind the ray
+		let ray = Ray { origin: Vec3 { x: 0.0, y: 1.0, z: -5.0 }, ..ray };
+		assert!(cuboid().hits(ray, 0.0, f64::INFINITY).is_none());
+	}
+}
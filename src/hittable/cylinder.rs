@@ -0,0 +1,138 @@
+//! A cylinder is a tube with a circular cross section, like a pipe or a can.
+
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{disk, Aabb, Hit, Hittable};
+use crate::hittable::frame::{self, AxisFrame, NearestHit};
+use crate::material::Material;
+
+/// A finite cylinder around the axis from `start` to `end`, optionally closed by disks at both ends.
+///
+/// On the tube, the texture coordinate `u` is the angle around the axis and `v` runs from 0.0 at `start` to 1.0 at
+/// `end`. On the caps, `u` is the angle and `v` the distance from the axis relative to the radius.
+#[derive(Clone, Debug)]
+pub struct Cylinder {
+	pub start: Vec3,
+	pub end: Vec3,
+	pub radius: f64,
+	/// Whether the ends are closed. An open cylinder can be seen into from both ends.
+	pub capped: bool,
+	pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Cylinder {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let axis = self.end - self.start;
+		let height = axis.length();
+		let frame = AxisFrame::new(self.start, axis);
+		let local = frame.ray_to_local(ray);
+		let (origin, direction) = (local.origin, local.direction);
+		let mut nearest = NearestHit::new(t_min, t_max);
+
+		// Tube: x² + z² = r² between y = 0 and y = height
+		let a = direction.x * direction.x + direction.z * direction.z;
+		let half_b = origin.x * direction.x + origin.z * direction.z;
+		let c = origin.x * origin.x + origin.z * origin.z - self.radius * self.radius;
+		let discriminant = half_b * half_b - a * c;
+		if a > 1e-12 && discriminant >= 0.0 {
+			for t in [(-half_b - discriminant.sqrt()) / a, (-half_b + discriminant.sqrt()) / a] {
+				let point = local.at(t);
+				if (0.0..=height).contains(&point.y) {
+					nearest.consider(t, || {
+						let normal = Vec3 { x: point.x / self.radius, y: 0.0, z: point.z / self.radius };
+						(normal, [frame::angle_around_axis(point), point.y / height])
+					});
+				}
+			}
+		}
+
+		if self.capped && direction.y.abs() > 1e-12 {
+			for (y, normal_y) in [(0.0, -1.0), (height, 1.0)] {
+				let t = (y - origin.y) / direction.y;
+				let point = local.at(t);
+				let distance = (point.x * point.x + point.z * point.z).sqrt();
+				if distance <= self.radius {
+					nearest.consider(t, || {
+						let normal = Vec3 { x: 0.0, y: normal_y, z: 0.0 };
+						(normal, [frame::angle_around_axis(point), distance / self.radius])
+					});
+				}
+			}
+		}
+
+		nearest.hit(ray, &frame, &self.material)
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		let extent = disk::extent((self.end - self.start).unit_vector(), self.radius);
+		Some(Aabb::from_points(self.start - extent, self.start + extent).surrounding(
+			&Aabb::from_points(self.end - extent, self.end + extent),
+		))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	/// Cylinder of radius 1.0 along the x axis from x = 0.0 to x = 4.0.
+	fn cylinder(capped: bool) -> Cylinder {
+		Cylinder {
+			start: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			end: Vec3 { x: 4.0, y: 0.0, z: 0.0 },
+			radius: 1.0,
+			capped,
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	#[test]
+	fn tube() {
+		let ray = Ray {
+			origin: Vec3 { x: 1.0, y: 5.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = cylinder(true).hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 4.0).abs() < 1e-9);
+		hit.normal.assert_near(Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		assert!((hit.uv[1] - 0.25).abs() < 1e-9);
+
+		// Beyond the end
+		let ray = Ray { origin: Vec3 { x: 4.5, y: 5.0, z: 0.0 }, ..ray };
+		assert!(cylinder(true).hits(ray, 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn caps() {
+		let ray = Ray {
+			origin: Vec3 { x: -2.0, y: 0.5, z: 0.0 },
+			direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = cylinder(true).hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.0).abs() < 1e-9);
+		hit.normal.assert_near(Vec3 { x: -1.0, y: 0.0, z: 0.0 });
+		assert!((hit.uv[1] - 0.5).abs() < 1e-9);
+
+		// Without caps, the ray passes through the tube and only hits it from the inside in the back
+		let ray = Ray { direction: Vec3 { x: 1.0, y: 0.1, z: 0.0 }, ..ray };
+		let hit = cylinder(false).hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+		hit.normal.assert_near(Vec3 { x: 0.0, y: -1.0, z: 0.0 });
+		assert!((hit.point.y - 1.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn bounding_box() {
+		let bounding_box = cylinder(true).bounding_box().unwrap();
+		bounding_box.min.assert_near(Vec3 { x: 0.0, y: -1.0, z: -1.0 });
+		bounding_box.max.assert_near(Vec3 { x: 4.0, y: 1.0, z: 1.0 });
+	}
+}
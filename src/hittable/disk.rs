@@ -0,0 +1,100 @@
+//! A disk is a flat circle, like a round table top or a spot light.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{plane, Aabb, Hit, Hittable};
+use crate::material::Material;
+
+/// A flat circle around `center` with the given `radius`, whose front side faces towards `normal`.
+///
+/// The texture coordinate `u` is the angle around the center (from 0.0 to 1.0 for a full turn) and `v` is the
+/// distance from the center (from 0.0 in the center to 1.0 on the rim).
+#[derive(Clone, Debug)]
+pub struct Disk {
+	pub center: Vec3,
+	/// Unit normal of the front side.
+	pub normal: Vec3,
+	pub radius: f64,
+	pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Disk {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let t = plane::intersect(self.center, self.normal, ray, t_min, t_max)?;
+		let offset = ray.at(t) - self.center;
+		let distance = offset.length();
+		if distance > self.radius {
+			return None;
+		}
+		let (tangent, bitangent) = self.normal.orthonormal_basis();
+		let angle = f64::atan2(offset.dot(bitangent), offset.dot(tangent)).rem_euclid(2.0 * PI);
+		let uv = [angle / (2.0 * PI), distance / self.radius];
+		Some(plane::hit(ray, t, uv, self.normal, &self.material))
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		let extent = extent(self.normal, self.radius);
+		Some(Aabb::from_points(self.center - extent, self.center + extent))
+	}
+}
+
+/// Returns how far a circle with the unit `normal` and the `radius` extends from its center along each axis.
+pub(crate) fn extent(normal: Vec3, radius: f64) -> Vec3 {
+	// The extent along an axis shrinks the more the circle faces that axis
+	Vec3 {
+		x: (1.0 - normal.x * normal.x).max(0.0).sqrt(),
+		y: (1.0 - normal.y * normal.y).max(0.0).sqrt(),
+		z: (1.0 - normal.z * normal.z).max(0.0).sqrt(),
+	} * radius
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	fn disk() -> Disk {
+		Disk {
+			center: Vec3 { x: 0.0, y: 0.0, z: -2.0 },
+			normal: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			radius: 1.0,
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	fn ray(x: f64, y: f64) -> Ray {
+		Ray {
+			origin: Vec3 { x, y, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		}
+	}
+
+	#[test]
+	fn hits() {
+		let hit = disk().hits(ray(0.0, 0.5), 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 2.0);
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		// Facing +z, the angle starts at +x and goes towards +y
+		assert_eq!(hit.uv, [0.25, 0.5]);
+		assert_eq!(disk().hits(ray(0.5, 0.0), 0.0, f64::INFINITY).unwrap().uv, [0.0, 0.5]);
+
+		assert!(disk().hits(ray(0.8, 0.8), 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn bounding_box() {
+		let bounding_box = disk().bounding_box().unwrap();
+		assert_eq!(bounding_box.extent(), Vec3 { x: 2.0, y: 2.0, z: 0.0 });
+
+		let tilted = Disk { normal: Vec3 { x: 1.0, y: 1.0, z: 0.0 }.unit_vector(), ..disk() };
+		let extent = tilted.bounding_box().unwrap().extent();
+		assert!((extent - Vec3 { x: 2f64.sqrt(), y: 2f64.sqrt(), z: 2.0 }).length() < 1e-9);
+	}
+}
@@ -0,0 +1,100 @@
+//! Local coordinate systems of objects that are rotationally symmetric around an axis, in which their equations
+//! are simple.
+
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{plane, Hit};
+use crate::material::Material;
+
+/// Orthonormal coordinate system whose y axis is the symmetry axis of an object.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct AxisFrame {
+	origin: Vec3,
+	x: Vec3,
+	y: Vec3,
+	z: Vec3,
+}
+
+impl AxisFrame {
+	/// Creates the coordinate system with its origin at `origin` and its y axis along `axis`, which doesn't need to
+	/// be normalized.
+	pub(crate) fn new(origin: Vec3, axis: Vec3) -> Self {
+		let y = axis.unit_vector();
+		// The basis is right-handed with y × z = x, so that x × y = z
+		let (z, x) = y.orthonormal_basis();
+		Self { origin, x, y, z }
+	}
+
+	/// Returns the [Ray] in local coordinates. The frame doesn't scale, so distances along the ray stay the same.
+	pub(crate) fn ray_to_local(&self, ray: Ray) -> Ray {
+		let offset = ray.origin - self.origin;
+		Ray {
+			origin: Vec3 { x: offset.dot(self.x), y: offset.dot(self.y), z: offset.dot(self.z) },
+			direction: Vec3 { x: ray.direction.dot(self.x), y: ray.direction.dot(self.y), z: ray.direction.dot(self.z) },
+			..ray
+		}
+	}
+
+	/// Returns the direction given in local coordinates, e.g. a normal, in world coordinates.
+	pub(crate) fn vector_to_world(&self, vector: Vec3) -> Vec3 {
+		self.x * vector.x + self.y * vector.y + self.z * vector.z
+	}
+}
+
+/// Keeps the nearest of several candidate intersections with the surfaces of an object, in local coordinates.
+pub(crate) struct NearestHit {
+	t_min: f64,
+	t_max: f64,
+	/// Distance along the ray, outward normal in local coordinates and texture coordinates.
+	nearest: Option<(f64, Vec3, [f64; 2])>,
+}
+
+impl NearestHit {
+	pub(crate) fn new(t_min: f64, t_max: f64) -> Self {
+		Self { t_min, t_max, nearest: None }
+	}
+
+	/// Keeps the intersection at distance `t` if it is in range and nearer than the ones before. `surface` returns
+	/// the outward unit normal and the texture coordinates, and is only called if the intersection is kept.
+	pub(crate) fn consider(&mut self, t: f64, surface: impl FnOnce() -> (Vec3, [f64; 2])) {
+		let in_range = (self.t_min..self.t_max).contains(&t);
+		if in_range && self.nearest.is_none_or(|(nearest_t, _, _)| t < nearest_t) {
+			let (normal, uv) = surface();
+			self.nearest = Some((t, normal, uv));
+		}
+	}
+
+	/// Builds the [Hit] of the nearest intersection of the world space `ray`, if any.
+	pub(crate) fn hit(self, ray: Ray, frame: &AxisFrame, material: &Arc<dyn Material>) -> Option<Hit> {
+		let (t, normal, uv) = self.nearest?;
+		Some(plane::hit(ray, t, uv, frame.vector_to_world(normal), material))
+	}
+}
+
+/// Returns the angle of the local point around the y axis as a texture coordinate from 0.0 to 1.0.
+pub(crate) fn angle_around_axis(point: Vec3) -> f64 {
+	let angle = f64::atan2(point.z, point.x);
+	angle.rem_euclid(2.0 * std::f64::consts::PI) / (2.0 * std::f64::consts::PI)
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ray_to_local() {
+		let frame = AxisFrame::new(Vec3 { x: 1.0, y: 2.0, z: 3.0 }, Vec3 { x: 0.0, y: 0.0, z: -2.0 });
+		let ray = Ray {
+			origin: Vec3 { x: 1.0, y: 2.0, z: 0.0 },
+			direction: Vec3 { x: 1.0, y: 1.0, z: 1.0 },
+			time: 0.0,
+		};
+		let local = frame.ray_to_local(ray);
+		assert!((local.origin - Vec3 { x: 0.0, y: 3.0, z: 0.0 }).is_near_zero());
+		assert!((local.direction.length() - ray.direction.length()).abs() < 1e-12);
+		assert!((frame.vector_to_world(local.direction) - ray.direction).is_near_zero());
+		assert!((frame.x.cross(frame.y) - frame.z).is_near_zero());
+	}
+}
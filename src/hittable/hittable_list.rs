@@ -1,9 +1,9 @@
 //! Implements [`Hittable`] for a list of [`Hittable`]s.
 
-use crate::hittable::{Hit, Hittable, Ray};
+use crate::hittable::{Aabb, Hit, Hittable, Ray};
 
 // Implements hittable for a vector of hittable objects (or some sort of reference).
-impl<H: AsRef<dyn Hittable>> Hittable for &[H] {
+impl<H: AsRef<dyn Hittable> + Sync> Hittable for &[H] {
 	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
 		self.iter()
 			// Check whether the ray hits the hittable object
@@ -11,11 +11,19 @@ impl<H: AsRef<dyn Hittable>> Hittable for &[H] {
 			// Find the hit with the smallest t value
 			.min_by(|hit1, hit2| hit1.t.total_cmp(&hit2.t))
 	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		// The list is unbounded as soon as one of its hittables is
+		self.iter()
+			.map(|hittable| hittable.as_ref().bounding_box())
+			.reduce(|box1, box2| Some(box1?.surrounding(&box2?)))
+			.flatten()
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use std::rc::Rc;
+	use std::sync::Arc;
 
 	use crate::{hittable::Sphere, material, Vec3};
 
@@ -28,17 +36,18 @@ mod tests {
 			Box::new(Sphere {
 				center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
 				radius: 1.0,
-				material: Rc::new(material::Lambertian::default()),
+				material: Arc::new(material::Lambertian::default()),
 			}),
 			Box::new(Sphere {
 				center: Vec3 { x: 0.0, y: 0.0, z: 2.0 },
 				radius: 1.0,
-				material: Rc::new(material::Lambertian::default()),
+				material: Arc::new(material::Lambertian::default()),
 			}),
 		];
 		let ray = Ray {
 			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
 			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
 		};
 
 		let hit = hittables.as_slice().hits(ray, 0.0, f64::INFINITY);
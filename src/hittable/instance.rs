@@ -0,0 +1,114 @@
+//! Instances place a shared object anywhere in the world, e.g. to fill a scene with copies of one mesh.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Ray, Transform, Vec3};
+use crate::hittable::{Aabb, Hit, Hittable};
+
+/// Places the `object` in the world by transforming it from its own coordinates with `transform`.
+///
+/// The object is shared, so that any number of instances can reference the same mesh without copying it.
+#[derive(Clone)]
+pub struct Instance {
+	pub object: Arc<dyn Hittable>,
+	/// Transformation from the coordinates of the object to those of the world.
+	pub transform: Transform,
+}
+
+impl Hittable for Instance {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		// Intersect in the coordinates of the object. The direction isn't normalized, so `t` stays the same.
+		let world_to_object = self.transform.inverse();
+		let object_ray = Ray {
+			origin: world_to_object.point(ray.origin),
+			direction: world_to_object.vector(ray.direction),
+			..ray
+		};
+		let hit = self.object.hits(object_ray, t_min, t_max)?;
+		Some(Hit {
+			point: self.transform.point(hit.point),
+			normal: self.transform.normal(hit.normal).unit_vector(),
+			..hit
+		})
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		// Encloses the transformed corners of the object's box
+		let Aabb { min, max } = self.object.bounding_box()?;
+		let corner = |index: usize| self.transform.point(Vec3 {
+			x: if index & 1 == 0 { min.x } else { max.x },
+			y: if index & 2 == 0 { min.y } else { max.y },
+			z: if index & 4 == 0 { min.z } else { max.z },
+		});
+		Some((1..8).fold(Aabb::from_points(corner(0), corner(0)), |bounding_box, index| {
+			bounding_box.including(corner(index))
+		}))
+	}
+}
+
+impl fmt::Debug for Instance {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Instance")
+			.field("transform", &self.transform)
+			.finish_non_exhaustive()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::{IntersectionSide, Sphere};
+	use crate::material;
+
+	use super::*;
+
+	/// Unit sphere stretched to an ellipsoid with radii (2, 1, 1) around (0, 0, -5).
+	fn ellipsoid() -> Instance {
+		let sphere = Sphere {
+			center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			radius: 1.0,
+			material: Arc::new(material::Lambertian::default()),
+		};
+		let transform = Transform::scaling(Vec3 { x: 2.0, y: 1.0, z: 1.0 }).unwrap()
+			.then(&Transform::translation(Vec3 { x: 0.0, y: 0.0, z: -5.0 }));
+		Instance { object: Arc::new(sphere), transform }
+	}
+
+	#[test]
+	fn hits() {
+		let ellipsoid = ellipsoid();
+		let ray = Ray {
+			origin: Vec3 { x: 1.5, y: 0.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let hit = ellipsoid.hits(ray, 0.0, f64::INFINITY).unwrap();
+		// (x / 2)² + z² = 1 at x = 1.5
+		let z = (1.0 - 0.75f64 * 0.75).sqrt();
+		assert!((hit.point - Vec3 { x: 1.5, y: 0.0, z: -5.0 + z }).length() < 1e-9);
+		assert!((hit.t - (5.0 - z)).abs() < 1e-9);
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		// The normal of the ellipsoid is perpendicular to its surface, not pointing away from its center
+		let expected_normal = Vec3 { x: 1.5 / 4.0, y: 0.0, z }.unit_vector();
+		assert!((hit.normal - expected_normal).length() < 1e-9);
+
+		let beside = Ray { origin: Vec3 { x: 2.5, y: 0.0, z: 0.0 }, ..ray };
+		assert!(ellipsoid.hits(beside, 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn bounding_box() {
+		let bounding_box = ellipsoid().bounding_box().unwrap();
+		assert_eq!(bounding_box.min, Vec3 { x: -2.0, y: -1.0, z: -6.0 });
+		assert_eq!(bounding_box.max, Vec3 { x: 2.0, y: 1.0, z: -4.0 });
+
+		let rotated = Instance {
+			transform: Transform::rotation(Vec3 { x: 0.0, y: 0.0, z: 1.0 }, 45.0).unwrap(),
+			..ellipsoid()
+		};
+		// Encloses the rotated box of the sphere, which is larger than the box of the rotated sphere
+		let extent = rotated.bounding_box().unwrap().extent();
+		assert!((extent.x - 2.0 * 2f64.sqrt()).abs() < 1e-9);
+	}
+}
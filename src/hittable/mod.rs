@@ -1,14 +1,49 @@
 //! Components for rays hitting objects.
 
 
-use std::rc::Rc;
+use std::sync::Arc;
 
+pub use aabb::Aabb;
+pub use bvh::Bvh;
+pub use capsule::Capsule;
+pub use cone::Cone;
+pub use constant_medium::ConstantMedium;
+pub use csg::{Csg, Interval, Operation};
+pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use instance::Instance;
+pub use moving::Moving;
+pub use plane::Plane;
+pub use quad::Quad;
+pub use sdf::Sdf;
 pub use sphere::Sphere;
+pub use torus::Torus;
+pub use triangle::Triangle;
+pub use triangle_mesh::{MeshError, TriangleMesh};
 
 use crate::{Ray, Vec3};
 use crate::material::Material;
 
 pub mod sphere;
+pub mod aabb;
+pub mod bvh;
+pub mod instance;
+pub mod moving;
+pub mod triangle;
+pub mod triangle_mesh;
+pub mod plane;
+pub mod quad;
+pub mod disk;
+pub mod cuboid;
+pub mod cylinder;
+pub mod cone;
+pub mod capsule;
+pub mod torus;
+pub mod constant_medium;
+pub mod csg;
+pub mod sdf;
+mod frame;
 mod hittable_list;
 
 /// Whether the [Ray] intersects the the object from the inside or outside.
@@ -29,13 +64,30 @@ pub struct Hit {
 	pub t: f64,
 	/// Whether the ray intersects the the object from the inside or outside.
 	pub intersection_side: IntersectionSide,
-	pub material: Rc<dyn Material>,
+	/// The texture coordinates `u` and `v` of the point on the surface, usually between 0.0 and 1.0.
+	pub uv: [f64; 2],
+	pub material: Arc<dyn Material>,
 }
 
 /// A trait for objects that can be hit by a ray.
-pub trait Hittable {
+///
+/// Hittables are shared between the render threads, so they have to be [Send] and [Sync].
+pub trait Hittable: Send + Sync {
 	/// Returns the [Hit] point of the ray if it hits the object, otherwise returns `None`.
 	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit>;
+
+	/// Returns the [Aabb] enclosing the whole object, or `None` if the object is unbounded.
+	fn bounding_box(&self) -> Option<Aabb>;
+
+	/// Returns the intervals along the line of the ray that are inside the object and overlap the range from `t_min`
+	/// to `t_max`, ordered by distance. Their entries and exits may lie outside the range. Only closed objects have
+	/// an inside, which [Csg] needs to combine them.
+	///
+	/// By default, the intervals are found by collecting the hits one after the other, each entering or leaving the
+	/// object according to its [IntersectionSide].
+	fn intervals(&self, ray: Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+		csg::intervals_from_hits(self, ray, t_min, t_max)
+	}
 }
 
 /// Calculates whether the [Ray] intersects the object from the inside or outside.
@@ -0,0 +1,91 @@
+//! Objects moving during a frame, which appear blurred along their motion.
+
+use std::fmt;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{Aabb, Hit, Hittable};
+
+/// Moves a hittable in a straight line: It is at its own position at the time 0.0 and moved by `displacement` at
+/// the time 1.0.
+///
+/// A moving sphere is a [Sphere](crate::hittable::Sphere) wrapped in `Moving`.
+pub struct Moving {
+	pub object: Box<dyn Hittable>,
+	pub displacement: Vec3,
+}
+
+impl Moving {
+	/// Returns the offset of the object from its own position at the `time`.
+	fn offset(&self, time: f64) -> Vec3 {
+		self.displacement * time
+	}
+}
+
+impl Hittable for Moving {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		// Instead of moving the object, the ray is moved the opposite way
+		let offset = self.offset(ray.time);
+		let moved_ray = Ray { origin: ray.origin - offset, ..ray };
+		let hit = self.object.hits(moved_ray, t_min, t_max)?;
+		Some(Hit { point: hit.point + offset, ..hit })
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		// Encloses the object at the start and at the end of the motion, and therefore everywhere in between
+		let start = self.object.bounding_box()?;
+		let end = Aabb { min: start.min + self.displacement, max: start.max + self.displacement };
+		Some(start.surrounding(&end))
+	}
+}
+
+impl fmt::Debug for Moving {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Moving")
+			.field("displacement", &self.displacement)
+			.finish_non_exhaustive()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use crate::hittable::Sphere;
+	use crate::material;
+
+	use super::*;
+
+	fn moving_sphere() -> Moving {
+		Moving {
+			object: Box::new(Sphere {
+				center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+				radius: 1.0,
+				material: Arc::new(material::Lambertian::default()),
+			}),
+			displacement: Vec3 { x: 4.0, y: 0.0, z: 0.0 },
+		}
+	}
+
+	#[test]
+	fn hits_at_time() {
+		let sphere = moving_sphere();
+		let ray = |time| Ray {
+			origin: Vec3 { x: 2.0, y: 0.0, z: 5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time,
+		};
+		assert!(sphere.hits(ray(0.0), 0.0, f64::INFINITY).is_none());
+		let hit = sphere.hits(ray(0.5), 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 4.0);
+		assert_eq!(hit.point, Vec3 { x: 2.0, y: 0.0, z: 1.0 });
+		assert!(sphere.hits(ray(1.0), 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn bounding_box() {
+		let bounding_box = moving_sphere().bounding_box().unwrap();
+		assert_eq!(bounding_box.min, Vec3 { x: -1.0, y: -1.0, z: -1.0 });
+		assert_eq!(bounding_box.max, Vec3 { x: 5.0, y: 1.0, z: 1.0 });
+	}
+}
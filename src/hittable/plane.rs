@@ -0,0 +1,109 @@
+//! An infinite plane, e.g. for floors that extend to the horizon.
+
+use std::sync::Arc;
+
+use crate::{hittable, Ray, Vec3};
+use crate::hittable::{Aabb, Hit, Hittable};
+use crate::material::Material;
+
+/// An infinite plane through `point`, whose front side faces towards `normal`.
+///
+/// The texture coordinates are the coordinates of the hit point in the plane, measured in world units from `point`.
+#[derive(Clone, Debug)]
+pub struct Plane {
+	pub point: Vec3,
+	/// Unit normal of the front side.
+	pub normal: Vec3,
+	pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Plane {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let t = intersect(self.point, self.normal, ray, t_min, t_max)?;
+		let point = ray.at(t);
+		let (tangent, bitangent) = self.normal.orthonormal_basis();
+		let offset = point - self.point;
+		Some(hit(ray, t, [offset.dot(tangent), offset.dot(bitangent)], self.normal, &self.material))
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		None
+	}
+}
+
+/// Returns the distance along the [Ray] at which it crosses the plane through `point` with the given `normal`,
+/// if it lies between `t_min` and `t_max`.
+pub(crate) fn intersect(point: Vec3, normal: Vec3, ray: Ray, t_min: f64, t_max: f64) -> Option<f64> {
+	let denominator = normal.dot(ray.direction);
+	if denominator.abs() < 1e-12 {
+		// The ray is parallel to the plane
+		return None;
+	}
+	let t = (point - ray.origin).dot(normal) / denominator;
+	(t_min..t_max).contains(&t).then_some(t)
+}
+
+/// Builds the [Hit] at distance `t` along the [Ray] of a surface with the given `outward_normal` at that point.
+pub(crate) fn hit(ray: Ray, t: f64, uv: [f64; 2], outward_normal: Vec3, material: &Arc<dyn Material>) -> Hit {
+	let intersection_side = hittable::calc_intersection_side(ray, outward_normal);
+	Hit {
+		point: ray.at(t),
+		normal: hittable::calc_normal(intersection_side, outward_normal),
+		t,
+		intersection_side,
+		uv,
+		material: Arc::clone(material),
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	fn floor() -> Plane {
+		Plane {
+			point: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+			normal: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	#[test]
+	fn hits() {
+		let ray = Ray {
+			origin: Vec3 { x: 3.0, y: 1.0, z: 2.0 },
+			direction: Vec3 { x: 0.0, y: -2.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = floor().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 1.0);
+		assert_eq!(hit.point, Vec3 { x: 3.0, y: -1.0, z: 2.0 });
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		assert_eq!(hit.uv, [3.0, -2.0]);
+
+		// From below
+		let ray = Ray { origin: Vec3 { x: 0.0, y: -3.0, z: 0.0 }, direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 }, ..ray };
+		let hit = floor().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: -1.0, z: 0.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+	}
+
+	#[test]
+	fn misses() {
+		// Parallel and pointing away
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+			time: 0.0,
+		};
+		assert!(floor().hits(ray, 0.0, f64::INFINITY).is_none());
+		let ray = Ray { direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 }, ..ray };
+		assert!(floor().hits(ray, 0.0, f64::INFINITY).is_none());
+		assert!(floor().bounding_box().is_none());
+	}
+}
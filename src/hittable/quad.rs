@@ -0,0 +1,99 @@
+//! A quad is a parallelogram, like a rectangular wall or the light in the ceiling of a room.
+
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{plane, Aabb, Hit, Hittable};
+use crate::material::Material;
+
+/// A parallelogram with the corners `corner`, `corner + edges[0]`, `corner + edges[0] + edges[1]` and
+/// `corner + edges[1]`.
+///
+/// The front side is the one from which the corners appear in this order counter-clockwise. The texture coordinates
+/// run from 0.0 to 1.0 along the two edges.
+#[derive(Clone, Debug)]
+pub struct Quad {
+	pub corner: Vec3,
+	pub edges: [Vec3; 2],
+	pub material: Arc<dyn Material>,
+}
+
+impl Quad {
+	/// Returns the unit normal of the front side.
+	pub fn normal(&self) -> Vec3 {
+		self.edges[0].cross(self.edges[1]).unit_vector()
+	}
+}
+
+impl Hittable for Quad {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let [edge1, edge2] = self.edges;
+		let cross = edge1.cross(edge2);
+		let normal = cross.unit_vector();
+		let t = plane::intersect(self.corner, normal, ray, t_min, t_max)?;
+
+		// Coordinates of the hit point in the (not necessarily orthogonal) basis of the edges
+		let offset = ray.at(t) - self.corner;
+		let w = cross / cross.squared_length();
+		let u = w.dot(offset.cross(edge2));
+		let v = w.dot(edge1.cross(offset));
+		if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+			return None;
+		}
+		Some(plane::hit(ray, t, [u, v], normal, &self.material))
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		let [edge1, edge2] = self.edges;
+		Some(
+			Aabb::from_points(self.corner, self.corner + edge1 + edge2)
+				.including(self.corner + edge1)
+				.including(self.corner + edge2),
+		)
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	/// Slanted parallelogram in the xy plane, facing +z.
+	fn quad() -> Quad {
+		Quad {
+			corner: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			edges: [Vec3 { x: 2.0, y: 0.0, z: 0.0 }, Vec3 { x: 1.0, y: 1.0, z: 0.0 }],
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	fn ray(x: f64, y: f64) -> Ray {
+		Ray {
+			origin: Vec3 { x, y, z: 3.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		}
+	}
+
+	#[test]
+	fn hits() {
+		let hit = quad().hits(ray(2.0, 0.5), 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 3.0);
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		assert_eq!(hit.uv, [0.75, 0.5]);
+		assert_eq!(quad().normal(), Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+	}
+
+	#[test]
+	fn misses() {
+		// Inside the bounding box, but left of the slanted edge
+		assert!(quad().hits(ray(0.25, 0.5), 0.0, f64::INFINITY).is_none());
+		assert!(quad().hits(ray(2.0, 1.5), 0.0, f64::INFINITY).is_none());
+		let bounding_box = quad().bounding_box().unwrap();
+		assert_eq!((bounding_box.min, bounding_box.max), (Vec3::ZERO, Vec3 { x: 3.0, y: 1.0, z: 0.0 }));
+	}
+}
@@ -0,0 +1,297 @@
+//! Objects given by a signed distance function, which are rendered by sphere tracing. This allows procedural
+//! shapes without a closed-form intersection, like smoothly blended objects or fractals.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{plane, Aabb, Hit, Hittable};
+use crate::material::Material;
+
+/// Signed distance from a point to the surface of a shape: positive outside, negative inside and 0.0 on the
+/// surface. It may underestimate the distance, but never overestimate it.
+pub type DistanceFunction = dyn Fn(Vec3) -> f64 + Send + Sync;
+
+/// Shape whose surface is where the `distance` function is 0.0, inside the `bounds`.
+///
+/// Rays are marched in steps as large as the distance to the surface, until they are closer than `epsilon` or
+/// leave the bounds. Rays passing closely along the surface take many steps, and are considered to miss it after
+/// `max_steps`.
+pub struct Sdf {
+	pub distance: Arc<DistanceFunction>,
+	/// Box containing the whole shape, to which the marching is limited.
+	pub bounds: Aabb,
+	/// Distance to the surface at which it counts as hit.
+	pub epsilon: f64,
+	pub max_steps: usize,
+	pub material: Arc<dyn Material>,
+}
+
+impl Sdf {
+	/// Estimates the outward normal at the `point` from the gradient of the distance function. Returns `None` where
+	/// the gradient vanishes, e.g. right in a crease between blended shapes.
+	fn normal(&self, point: Vec3) -> Option<Vec3> {
+		let distance = |x, y, z| (self.distance)(point + Vec3 { x, y, z });
+		let h = self.epsilon;
+		let gradient = Vec3 {
+			x: distance(h, 0.0, 0.0) - distance(-h, 0.0, 0.0),
+			y: distance(0.0, h, 0.0) - distance(0.0, -h, 0.0),
+			z: distance(0.0, 0.0, h) - distance(0.0, 0.0, -h),
+		};
+		// The gradient is scaled by 2h. NaNs of the distance function count as no gradient.
+		(gradient.length() > 1e-6 * h).then(|| gradient.unit_vector())
+	}
+}
+
+impl Hittable for Sdf {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let (mut t, t_end) = self.bounds.clip(ray, t_min, t_max)?;
+		let speed = ray.direction.length();
+
+		// Rays entering the bounds march towards the surface from the outside
+		let mut side = 1.0;
+		let mut previous_distance = f64::INFINITY;
+		if t <= t_min {
+			// Rays starting inside the bounds may also start inside the shape. A ray starting on the surface, e.g. one
+			// scattered by it, marches on the side it is heading to.
+			let start_distance = (self.distance)(ray.at(t));
+			side = if start_distance.abs() >= self.epsilon {
+				start_distance.signum()
+			} else if self.normal(ray.at(t)).is_some_and(|normal| normal.dot(ray.direction) < 0.0) {
+				-1.0
+			} else {
+				1.0
+			};
+			previous_distance = side * start_distance;
+			t += previous_distance.max(self.epsilon) / speed;
+		}
+
+		// The surface is hit once the distance falls below epsilon. It has to decrease, so that a ray starting on the
+		// surface doesn't hit it again right away.
+		for _ in 0..self.max_steps {
+			if t > t_end {
+				return None;
+			}
+			let point = ray.at(t);
+			let distance = side * (self.distance)(point);
+			if distance < self.epsilon && distance < previous_distance {
+				// Without a gradient, the surface is taken to face the ray
+				let normal = self.normal(point).unwrap_or(-ray.direction / speed);
+				return Some(plane::hit(ray, t, [0.0, 0.0], normal, &self.material));
+			}
+			t += distance.max(self.epsilon) / speed;
+			previous_distance = distance;
+		}
+		None
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		Some(self.bounds)
+	}
+}
+
+impl fmt::Debug for Sdf {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Sdf")
+			.field("bounds", &self.bounds)
+			.field("epsilon", &self.epsilon)
+			.field("max_steps", &self.max_steps)
+			.field("material", &self.material)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Returns the distance function of a sphere.
+pub fn sphere(center: Vec3, radius: f64) -> Arc<DistanceFunction> {
+	Arc::new(move |point| (point - center).length() - radius)
+}
+
+/// Returns the distance function of the axis-aligned box from `min` to `max` whose edges and corners are rounded off
+/// with the `radius`.
+pub fn rounded_box(min: Vec3, max: Vec3, radius: f64) -> Arc<DistanceFunction> {
+	let center = (min + max) * 0.5;
+	let inner_half_extent = (max - min) * 0.5 - Vec3 { x: radius, y: radius, z: radius };
+	Arc::new(move |point| {
+		let offset = point - center;
+		let q = Vec3 { x: offset.x.abs(), y: offset.y.abs(), z: offset.z.abs() } - inner_half_extent;
+		let outside = q.max(Vec3 { x: 0.0, y: 0.0, z: 0.0 }).length();
+		let inside = q.x.max(q.y).max(q.z).min(0.0);
+		outside + inside - radius
+	})
+}
+
+/// Returns the distance function of the union of the shapes, blended into each other where they are closer than
+/// `smoothness`. A smoothness of 0.0 is the plain union.
+///
+/// The blend stays within `smoothness / 4.0` of the shapes.
+pub fn smooth_union(shapes: Vec<Arc<DistanceFunction>>, smoothness: f64) -> Arc<DistanceFunction> {
+	Arc::new(move |point| {
+		shapes.iter()
+			.map(|shape| shape(point))
+			.reduce(|a, b| {
+				// Polynomial smooth minimum, which only differs from the minimum where a and b are closer than smoothness
+				let h = (smoothness - (a - b).abs()).max(0.0);
+				a.min(b) - if h > 0.0 { h * h / (4.0 * smoothness) } else { 0.0 }
+			})
+			.unwrap_or(f64::INFINITY)
+	})
+}
+
+/// Returns the distance estimate of the Mandelbulb fractal of the given `power`, scaled by `scale` around the
+/// `center`. It lies within `2.0 * scale` of the center, since all points further away escape. More `iterations` show
+/// finer detail.
+pub fn mandelbulb(center: Vec3, scale: f64, power: f64, iterations: usize) -> Arc<DistanceFunction> {
+	Arc::new(move |point| {
+		let c = (point - center) / scale;
+		let mut z = c;
+		// Derivative of the iteration, for the distance estimate
+		let mut derivative = 1.0;
+		let mut radius = z.length();
+		for _ in 0..iterations {
+			if radius > 2.0 {
+				break;
+			}
+			// z ↦ z^power + c in spherical coordinates
+			let theta = f64::atan2((z.x * z.x + z.y * z.y).sqrt(), z.z) * power;
+			let phi = f64::atan2(z.y, z.x) * power;
+			derivative = power * radius.powf(power - 1.0) * derivative + 1.0;
+			let length = radius.powf(power);
+			z = Vec3 {
+				x: theta.sin() * phi.cos(),
+				y: theta.sin() * phi.sin(),
+				z: theta.cos(),
+			} * length + c;
+			radius = z.length();
+		}
+		if radius == 0.0 {
+			// Deep inside
+			return -scale;
+		}
+		0.5 * radius.ln() * radius / derivative * scale
+	})
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::{IntersectionSide, Sphere};
+	use crate::material;
+
+	use super::*;
+
+	fn sdf(distance: Arc<DistanceFunction>, bounds: Aabb) -> Sdf {
+		Sdf {
+			distance,
+			bounds,
+			epsilon: 1e-6,
+			max_steps: 1000,
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	fn unit_sphere() -> Sdf {
+		let center = Vec3 { x: 0.0, y: 0.0, z: 0.0 };
+		let bounds = Aabb::from_points(Vec3 { x: -1.0, y: -1.0, z: -1.0 }, Vec3 { x: 1.0, y: 1.0, z: 1.0 });
+		sdf(sphere(center, 1.0), bounds)
+	}
+
+	#[test]
+	fn like_sphere() {
+		let analytic = Sphere {
+			center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			radius: 1.0,
+			material: Arc::new(material::Lambertian::default()),
+		};
+		let rays = [
+			(Vec3 { x: 0.0, y: 0.0, z: -5.0 }, Vec3 { x: 0.0, y: 0.0, z: 2.0 }),
+			(Vec3 { x: 3.0, y: 2.0, z: 1.0 }, Vec3 { x: -3.0, y: -1.5, z: -1.0 }),
+			// From the inside
+			(Vec3 { x: 0.2, y: 0.1, z: 0.0 }, Vec3 { x: 0.0, y: 1.0, z: 1.0 }),
+		];
+		for (origin, direction) in rays {
+			let ray = Ray { origin, direction, time: 0.0 };
+			let expected = analytic.hits(ray, 0.0, f64::INFINITY).unwrap();
+			let hit = unit_sphere().hits(ray, 0.0, f64::INFINITY).unwrap();
+			assert!((hit.t - expected.t).abs() < 1e-5, "{} != {}", hit.t, expected.t);
+			assert!((hit.normal - expected.normal).length() < 1e-4);
+			assert_eq!(hit.intersection_side, expected.intersection_side);
+		}
+
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 1.5, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
+		};
+		assert!(unit_sphere().hits(ray, 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn scattered_from_surface() {
+		let hit_point = Vec3 { x: 0.0, y: 0.0, z: -1.0 };
+		// Reflected away from the surface, nothing else is hit
+		let ray = Ray { origin: hit_point, direction: Vec3 { x: 0.0, y: 1.0, z: -1.0 }, time: 0.0 };
+		assert!(unit_sphere().hits(ray, 0.0001, f64::INFINITY).is_none());
+		// Refracted into the sphere, it is left on the other side
+		let ray = Ray { direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 }, ..ray };
+		let hit = unit_sphere().hits(ray, 0.0001, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.0).abs() < 1e-5);
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+	}
+
+	#[test]
+	fn without_gradient() {
+		let spheres = vec![sphere(Vec3 { x: -1.0, y: 0.0, z: 0.0 }, 1.0), sphere(Vec3 { x: 1.0, y: 0.0, z: 0.0 }, 1.0)];
+		let bounds = Aabb::from_points(Vec3 { x: -2.0, y: -1.0, z: -1.0 }, Vec3 { x: 2.0, y: 1.0, z: 1.0 });
+		// Where the spheres touch, the distance increases in all directions
+		assert!(sdf(smooth_union(spheres, 0.0), bounds).normal(Vec3 { x: 0.0, y: 0.0, z: 0.0 }).is_none());
+
+		// A shape filling its bounds without any gradient is hit where the ray enters them, facing the ray
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 2.0 },
+			time: 0.0,
+		};
+		let hit = sdf(Arc::new(|_| 0.0), bounds).hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!((hit.t, hit.normal), (2.0, Vec3 { x: 0.0, y: 0.0, z: -1.0 }));
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+	}
+
+	#[test]
+	fn step_limit() {
+		// Passing closely along the surface takes more steps than allowed
+		let ray = Ray {
+			origin: Vec3 { x: -1.0, y: 1.0001, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
+		};
+		let bounds = Aabb::from_points(Vec3 { x: -2.0, y: -2.0, z: -2.0 }, Vec3 { x: 2.0, y: 2.0, z: 2.0 });
+		let shape = rounded_box(Vec3 { x: -1.0, y: -1.0, z: -1.0 }, Vec3 { x: 1.0, y: 1.0, z: 1.0 }, 0.1);
+		assert!(sdf(Arc::clone(&shape), bounds).hits(ray, 0.0, f64::INFINITY).is_none());
+		let ray = Ray { origin: Vec3 { x: 0.0, y: 0.5, z: -5.0 }, ..ray };
+		let limited = Sdf { max_steps: 1, ..sdf(shape, bounds) };
+		assert!(limited.hits(ray, 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn distance_functions() {
+		let shape = rounded_box(Vec3 { x: -1.0, y: -2.0, z: -1.0 }, Vec3 { x: 1.0, y: 2.0, z: 1.0 }, 0.5);
+		assert!((shape(Vec3 { x: 3.0, y: 0.0, z: 0.0 }) - 2.0).abs() < 1e-12);
+		assert!((shape(Vec3 { x: 0.0, y: 0.0, z: 0.0 }) + 1.0).abs() < 1e-12);
+		// The corner is rounded off
+		let corner = Vec3 { x: 0.5, y: 1.5, z: 0.5 } + Vec3 { x: 1.0, y: 1.0, z: 1.0 }.unit_vector() * 0.5;
+		assert!(shape(corner).abs() < 1e-12);
+
+		let spheres = vec![sphere(Vec3 { x: -1.0, y: 0.0, z: 0.0 }, 1.0), sphere(Vec3 { x: 1.0, y: 0.0, z: 0.0 }, 1.0)];
+		let union = smooth_union(spheres, 0.5);
+		// Blended where the spheres meet, unchanged far from it
+		assert!((union(Vec3 { x: 0.0, y: 0.0, z: 0.0 }) + 0.125).abs() < 1e-12);
+		assert!((union(Vec3 { x: 3.0, y: 0.0, z: 0.0 }) - 1.0).abs() < 1e-12);
+		let spheres = vec![sphere(Vec3 { x: -1.0, y: 0.0, z: 0.0 }, 1.0), sphere(Vec3 { x: 1.0, y: 0.0, z: 0.0 }, 1.0)];
+		assert_eq!(smooth_union(spheres, 0.0)(Vec3 { x: 0.0, y: 0.0, z: 0.0 }), 0.0);
+
+		let bulb = mandelbulb(Vec3 { x: 0.0, y: 0.0, z: 0.0 }, 2.0, 8.0, 10);
+		assert!(bulb(Vec3 { x: 0.0, y: 0.0, z: 0.0 }) < 0.0);
+		let far = bulb(Vec3 { x: 10.0, y: 0.0, z: 0.0 });
+		assert!(far > 0.0 && far < 10.0);
+	}
+}
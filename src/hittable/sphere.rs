@@ -1,10 +1,10 @@
 //! A sphere is a 3-dimensional object with a center point and a radius, like a ball.
 
 use std::fmt::Debug;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{hittable, Ray, Vec3};
-use crate::hittable::{Hit, Hittable};
+use crate::hittable::{Aabb, Hit, Hittable};
 use crate::material::Material;
 
 /// A sphere is a 3-dimensional object with a center point and a radius, like a ball.
@@ -12,7 +12,19 @@ use crate::material::Material;
 pub struct Sphere {
 	pub center: Vec3,
 	pub radius: f64,
-	pub material: Rc<dyn Material>,
+	pub material: Arc<dyn Material>,
+}
+
+impl Sphere {
+	/// Returns the texture coordinates of the point on the unit sphere with the given normal.
+	///
+	/// `u` is the longitude, going around the y axis from -x over +z, +x and -z back to -x. `v` is the latitude,
+	/// from the south pole (-y) to the north pole (+y).
+	fn uv(normal: Vec3) -> [f64; 2] {
+		let longitude = f64::atan2(-normal.z, normal.x) + std::f64::consts::PI;
+		let latitude = f64::acos(-normal.y.clamp(-1.0, 1.0));
+		[longitude / (2.0 * std::f64::consts::PI), latitude / std::f64::consts::PI]
+	}
 }
 
 impl Hittable for Sphere {
@@ -45,7 +57,13 @@ impl Hittable for Sphere {
 		let outward_normal = (point - self.center) / self.radius;
 		let intersection_side = hittable::calc_intersection_side(ray, outward_normal);
 		let normal = hittable::calc_normal(intersection_side, outward_normal);
-		Some(Hit { point, normal, t, intersection_side, material: Rc::clone(&self.material) })
+		let uv = Self::uv(outward_normal);
+		Some(Hit { point, normal, t, intersection_side, uv, material: Arc::clone(&self.material) })
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		let radius = Vec3 { x: self.radius, y: self.radius, z: self.radius };
+		Some(Aabb::from_points(self.center - radius, self.center + radius))
 	}
 }
 
@@ -61,25 +79,38 @@ mod tests {
 		let sphere = Sphere {
 			center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
 			radius: 1.0,
-			material: Rc::new(material::Lambertian::default()),
+			material: Arc::new(material::Lambertian::default()),
 		};
 		let ray = Ray {
 			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
 			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
 		};
 		assert!(sphere.hits(ray, 0.0, f64::INFINITY).is_some());
 	}
 
+	#[test]
+	fn uv() {
+		let uv = |x, y, z| Sphere::uv(Vec3 { x, y, z });
+		assert_eq!(uv(-1.0, 0.0, 0.0), [0.0, 0.5]);
+		assert_eq!(uv(0.0, 0.0, 1.0), [0.25, 0.5]);
+		assert_eq!(uv(1.0, 0.0, 0.0), [0.5, 0.5]);
+		assert_eq!(uv(0.0, 0.0, -1.0), [0.75, 0.5]);
+		assert_eq!(uv(0.0, 1.0, 0.0)[1], 1.0);
+		assert_eq!(uv(0.0, -1.0, 0.0)[1], 0.0);
+	}
+
 	#[test]
 	fn misses() {
 		let sphere = Sphere {
 			center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
 			radius: 1.0,
-			material: Rc::new(material::Lambertian::default()),
+			material: Arc::new(material::Lambertian::default()),
 		};
 		let ray = Ray {
 			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
 			direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			time: 0.0,
 		};
 		assert!(sphere.hits(ray, 0.0, f64::INFINITY).is_none());
 	}
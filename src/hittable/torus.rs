@@ -0,0 +1,242 @@
+//! A torus is a ring with a circular cross section, like a donut or an O-ring.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{disk, Aabb, Hit, Hittable};
+use crate::hittable::frame::{self, AxisFrame, NearestHit};
+use crate::material::Material;
+
+/// A ring around `center` in the plane perpendicular to `axis`. Its tube with the `minor_radius` runs along the
+/// circle with the `major_radius`.
+///
+/// The texture coordinate `u` is the angle around the axis and `v` the angle around the tube, starting at its
+/// outer equator and going over the side of the ring that `axis` points to.
+#[derive(Clone, Debug)]
+pub struct Torus {
+	pub center: Vec3,
+	pub axis: Vec3,
+	pub major_radius: f64,
+	pub minor_radius: f64,
+	pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Torus {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let frame = AxisFrame::new(self.center, self.axis);
+		let local = frame.ray_to_local(ray);
+		// With a unit direction the coefficients stay well-conditioned. Distances along it are scaled back later.
+		let speed = local.direction.length();
+		let direction = local.direction / speed;
+
+		// The coefficients lose precision with the distance of the origin, so they are computed from the point of the
+		// ray closest to the center instead, which rays missing the bounding sphere don't get near enough to
+		let closest = -local.origin.dot(direction);
+		let origin = local.origin + direction * closest;
+		let bounding_radius = self.major_radius + self.minor_radius;
+		if origin.squared_length() > bounding_radius * bounding_radius {
+			return None;
+		}
+
+		// (|p|² - R² - r²)² + 4 R² (y² - r²) = 0 along the ray p = o + s d
+		let (major, minor) = (self.major_radius * self.major_radius, self.minor_radius * self.minor_radius);
+		let od = origin.dot(direction);
+		let k = origin.squared_length() - major - minor;
+		let coefficients = [
+			4.0 * od,
+			4.0 * od * od + 2.0 * k + 4.0 * major * direction.y * direction.y,
+			4.0 * k * od + 8.0 * major * origin.y * direction.y,
+			k * k + 4.0 * major * (origin.y * origin.y - minor),
+		];
+
+		let mut nearest = NearestHit::new(t_min, t_max);
+		for s in solve_quartic(coefficients).into_iter().flatten() {
+			let t = (closest + s) / speed;
+			nearest.consider(t, || {
+				let point = origin + direction * s;
+				// The normal points away from the nearest point on the circle through the middle of the tube
+				let radial = Vec3 { x: point.x, y: 0.0, z: point.z };
+				let ring_point = if radial.is_near_zero() { radial } else { radial.unit_vector() * self.major_radius };
+				let normal = (point - ring_point).unit_vector();
+				let tube_angle = f64::atan2(point.y, radial.length() - self.major_radius).rem_euclid(2.0 * PI);
+				(normal, [frame::angle_around_axis(point), tube_angle / (2.0 * PI)])
+			});
+		}
+		nearest.hit(ray, &frame, &self.material)
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		let axis = self.axis.unit_vector();
+		let extent = disk::extent(axis, self.major_radius)
+			+ Vec3 { x: self.minor_radius, y: self.minor_radius, z: self.minor_radius };
+		Some(Aabb::from_points(self.center - extent, self.center + extent))
+	}
+}
+
+/// Returns the real roots of the monic quartic polynomial `x⁴ + a x³ + b x² + c x + d`, given the coefficients
+/// `[a, b, c, d]`, with Ferrari's method.
+fn solve_quartic([a, b, c, d]: [f64; 4]) -> [Option<f64>; 4] {
+	// Substituting x = y - a/4 gives the depressed quartic y⁴ + p y² + q y + r
+	let shift = -a / 4.0;
+	let a2 = a * a;
+	let p = b - 3.0 * a2 / 8.0;
+	let q = c - a * b / 2.0 + a2 * a / 8.0;
+	let r = d - a * c / 4.0 + a2 * b / 16.0 - 3.0 * a2 * a2 / 256.0;
+
+	let mut roots = [None; 4];
+	// q is compared to the size of the roots cubed, which is estimated from p and r
+	let scale = p.abs().sqrt().max(r.abs().sqrt().sqrt());
+	if q.abs() <= 1e-12 * scale * scale * scale {
+		// Biquadratic: z² + p z + r with z = y²
+		for (index, z) in solve_quadratic(p, r).into_iter().flatten().enumerate() {
+			if z >= 0.0 {
+				roots[2 * index] = Some(z.sqrt() + shift);
+				roots[2 * index + 1] = Some(-z.sqrt() + shift);
+			}
+		}
+	} else {
+		// Adding 2 m y² + m² + m p to both sides turns the left side into the square (y² + p/2 + m)². The right side
+		// 2 m y² - q y + m² + m p + p²/4 - r is a square as well if m is a root of the resolvent cubic.
+		let m = largest_cubic_root(p, p * p / 4.0 - r, -q * q / 8.0);
+		if m <= 0.0 {
+			return roots;
+		}
+		let s = (2.0 * m).sqrt();
+		let factors = [(-s, p / 2.0 + m + q / (2.0 * s)), (s, p / 2.0 + m - q / (2.0 * s))];
+		for (index, (linear, constant)) in factors.into_iter().enumerate() {
+			let [root1, root2] = solve_quadratic(linear, constant);
+			roots[2 * index] = root1.map(|y| y + shift);
+			roots[2 * index + 1] = root2.map(|y| y + shift);
+		}
+	}
+
+	// Polish the roots with Newton's method, which fixes the loss of precision in the substitutions
+	let polynomial = |x: f64| (((x + a) * x + b) * x + c) * x + d;
+	let derivative = |x: f64| ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+	roots.map(|root| root.map(|mut x| {
+		for _ in 0..2 {
+			let slope = derivative(x);
+			if slope != 0.0 {
+				x -= polynomial(x) / slope;
+			}
+		}
+		x
+	}))
+}
+
+/// Returns the real roots of `x² + b x + c`.
+fn solve_quadratic(b: f64, c: f64) -> [Option<f64>; 2] {
+	let discriminant = b * b / 4.0 - c;
+	if discriminant < 0.0 {
+		return [None, None];
+	}
+	let root = discriminant.sqrt();
+	[Some(-b / 2.0 - root), Some(-b / 2.0 + root)]
+}
+
+/// Returns the largest real root of `x³ + a x² + b x + c`.
+fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+	// Substituting x = t - a/3 gives the depressed cubic t³ + p t + q
+	let p = b - a * a / 3.0;
+	let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+	let discriminant = q * q / 4.0 + p * p * p / 27.0;
+	let t = if discriminant > 0.0 {
+		// One real root (Cardano)
+		let root = discriminant.sqrt();
+		(-q / 2.0 + root).cbrt() + (-q / 2.0 - root).cbrt()
+	} else {
+		// Three real roots, the largest one of the trigonometric solution
+		let radius = (-p / 3.0).sqrt();
+		let cos = if radius == 0.0 { 0.0 } else { (-q / (2.0 * radius * radius * radius)).clamp(-1.0, 1.0) };
+		2.0 * radius * (cos.acos() / 3.0).cos()
+	};
+	t - a / 3.0
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	/// Ring around the y axis through x = ±2.0 with a tube of radius 0.5.
+	fn torus() -> Torus {
+		Torus {
+			center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			axis: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			major_radius: 2.0,
+			minor_radius: 0.5,
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	#[test]
+	fn quartic() {
+		// (x - 1)(x + 2)(x - 3)(x - 0.5) = x⁴ - 2.5 x³ - 4 x² + 8.5 x - 3
+		let mut roots: Vec<f64> = solve_quartic([-2.5, -4.0, 8.5, -3.0]).into_iter().flatten().collect();
+		roots.sort_by(f64::total_cmp);
+		let expected = [-2.0, 0.5, 1.0, 3.0];
+		assert_eq!(roots.len(), 4);
+		assert!(roots.iter().zip(expected).all(|(root, expected)| (root - expected).abs() < 1e-9), "{:?}", roots);
+		// x⁴ + 1 has no real roots
+		assert!(solve_quartic([0.0, 0.0, 0.0, 1.0]).iter().all(Option::is_none));
+	}
+
+	#[test]
+	fn hits() {
+		// Down onto the top of the tube
+		let ray = Ray {
+			origin: Vec3 { x: 2.0, y: 5.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: -2.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = torus().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.25).abs() < 1e-9);
+		hit.normal.assert_near(Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+		assert!((hit.uv[1] - 0.25).abs() < 1e-9);
+
+		// Through the hole
+		let ray = Ray { origin: Vec3 { x: 0.0, y: 5.0, z: 0.0 }, ..ray };
+		assert!(torus().hits(ray, 0.0, f64::INFINITY).is_none());
+
+		// Horizontally through both sides of the ring: the outer side first
+		let ray = Ray { origin: Vec3 { x: -5.0, y: 0.0, z: 0.0 }, direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 }, ..ray };
+		let hit = torus().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 2.5).abs() < 1e-9);
+		hit.normal.assert_near(Vec3 { x: -1.0, y: 0.0, z: 0.0 });
+		// And from inside the tube
+		let hit = torus().hits(ray, 3.0, f64::INFINITY).unwrap();
+		assert!((hit.t - 3.5).abs() < 1e-9);
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+	}
+
+	#[test]
+	fn distant_rays() {
+		let torus = Torus { major_radius: 1.0, minor_radius: 0.25, ..torus() };
+		// Aimed along the normal at points on the outer half of the tube, which nothing else is in front of
+		for distance in [500.0, 5000.0] {
+			for i in 0..100 {
+				let angle = i as f64 / 100.0 * 2.0 * PI;
+				let tube_angle = (i as f64 / 100.0 - 0.5) * 0.98 * PI;
+				let radial = Vec3 { x: angle.cos(), y: 0.0, z: angle.sin() };
+				let normal = radial * tube_angle.cos() + Vec3 { x: 0.0, y: tube_angle.sin(), z: 0.0 };
+				let point = radial * torus.major_radius + normal * torus.minor_radius;
+				let ray = Ray { origin: point + normal * distance, direction: -normal, time: 0.0 };
+				let hit = torus.hits(ray, 0.0, f64::INFINITY).unwrap();
+				hit.point.assert_near(point);
+			}
+		}
+	}
+
+	#[test]
+	fn bounding_box() {
+		let tilted = Torus { axis: Vec3 { x: 1.0, y: 0.0, z: 0.0 }, ..torus() };
+		let bounding_box = tilted.bounding_box().unwrap();
+		bounding_box.min.assert_near(Vec3 { x: -0.5, y: -2.5, z: -2.5 });
+		bounding_box.max.assert_near(Vec3 { x: 0.5, y: 2.5, z: 2.5 });
+	}
+}
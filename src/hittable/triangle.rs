@@ -0,0 +1,189 @@
+//! A triangle is the simplest flat surface, and the building block of polygon meshes.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::{hittable, Ray, Vec3};
+use crate::hittable::{Aabb, Hit, Hittable};
+use crate::material::Material;
+
+/// A triangle spanned by three vertices.
+///
+/// The front side is the one from which the vertices appear in counter-clockwise order.
+#[derive(Clone, Debug)]
+pub struct Triangle {
+	pub vertices: [Vec3; 3],
+	pub material: Arc<dyn Material>,
+}
+
+impl Triangle {
+	/// Returns the unit normal of the front side.
+	pub fn normal(&self) -> Vec3 {
+		let [v0, v1, v2] = self.vertices;
+		(v1 - v0).cross(v2 - v0).unit_vector()
+	}
+}
+
+impl Hittable for Triangle {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		// Without texture coordinates per vertex, the barycentric coordinates are used
+		let (t, u, v) = intersect(self.vertices, ray, t_min, t_max)?;
+		let normal = self.normal();
+		Some(hit(ray, t, [u, v], normal, normal, &self.material))
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		Some(bounding_box(self.vertices))
+	}
+}
+
+/// Intersects the [Ray] with the triangle spanned by the `vertices` with the Möller–Trumbore algorithm.
+///
+/// Returns the distance along the ray and the barycentric coordinates `u` and `v` of the hit point, i.e. the
+/// weights of the second and third vertex. The weight of the first vertex is `1 - u - v`.
+pub(crate) fn intersect(vertices: [Vec3; 3], ray: Ray, t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+	let [v0, v1, v2] = vertices;
+	let edge1 = v1 - v0;
+	let edge2 = v2 - v0;
+	let p = ray.direction.cross(edge2);
+	let determinant = edge1.dot(p);
+	// The determinant is the product of the lengths of the direction and edges and the sine of the angle between the
+	// ray and the triangle. Comparing the squares saves square roots.
+	let scale = ray.direction.squared_length() * edge1.squared_length() * edge2.squared_length();
+	if determinant * determinant <= 1e-24 * scale {
+		// The ray is parallel to the triangle, or the triangle is degenerate
+		return None;
+	}
+
+	let inverse_determinant = 1.0 / determinant;
+	let s = ray.origin - v0;
+	let u = s.dot(p) * inverse_determinant;
+	if !(0.0..=1.0).contains(&u) {
+		return None;
+	}
+	let q = s.cross(edge1);
+	let v = ray.direction.dot(q) * inverse_determinant;
+	if v < 0.0 || u + v > 1.0 {
+		return None;
+	}
+
+	let t = edge2.dot(q) * inverse_determinant;
+	if !(t_min..t_max).contains(&t) {
+		return None;
+	}
+	Some((t, u, v))
+}
+
+/// Builds the [Hit] of a triangle. Which side was hit is decided by the `geometric_normal` of the triangle, the
+/// `shading_normal` (e.g. interpolated from vertex normals) is flipped to the same side.
+pub(crate) fn hit(
+	ray: Ray,
+	t: f64,
+	uv: [f64; 2],
+	geometric_normal: Vec3,
+	shading_normal: Vec3,
+	material: &Arc<dyn Material>,
+) -> Hit {
+	let intersection_side = hittable::calc_intersection_side(ray, geometric_normal);
+	Hit {
+		point: ray.at(t),
+		normal: hittable::calc_normal(intersection_side, shading_normal),
+		t,
+		intersection_side,
+		uv,
+		material: Arc::clone(material),
+	}
+}
+
+/// Returns the [Aabb] of the triangle spanned by the `vertices`.
+pub(crate) fn bounding_box(vertices: [Vec3; 3]) -> Aabb {
+	Aabb::from_points(vertices[0], vertices[1]).including(vertices[2])
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::IntersectionSide;
+	use crate::material;
+
+	use super::*;
+
+	fn triangle() -> Triangle {
+		Triangle {
+			vertices: [
+				Vec3 { x: -1.0, y: -1.0, z: 0.0 },
+				Vec3 { x: 1.0, y: -1.0, z: 0.0 },
+				Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			],
+			material: Arc::new(material::Lambertian::default()),
+		}
+	}
+
+	#[test]
+	fn hits() {
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: 5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let hit = triangle().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 5.0);
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Outside);
+
+		// From behind
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
+		};
+		let hit = triangle().hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+		assert_eq!(hit.intersection_side, IntersectionSide::Inside);
+	}
+
+	#[test]
+	fn misses() {
+		// Beside the triangle
+		let ray = Ray {
+			origin: Vec3 { x: 0.9, y: 0.9, z: 5.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		assert!(triangle().hits(ray, 0.0, f64::INFINITY).is_none());
+		// Parallel to the triangle
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: -5.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			time: 0.0,
+		};
+		assert!(triangle().hits(ray, 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn small_scale() {
+		// A triangle of a few micrometers in meters, hit by a ray with a short direction vector
+		let vertices = triangle().vertices.map(|vertex| vertex * 1e-6);
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: 1e-6 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1e-6 },
+			time: 0.0,
+		};
+		let (t, _, _) = intersect(vertices, ray, 0.0, f64::INFINITY).unwrap();
+		assert!((t - 1.0).abs() < 1e-9);
+		// Degenerate triangles are still missed
+		let vertices = [vertices[0], vertices[1], vertices[1] * 2.0 - vertices[0]];
+		assert!(intersect(vertices, ray, 0.0, f64::INFINITY).is_none());
+	}
+
+	#[test]
+	fn barycentric_coordinates() {
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 1.0, z: 1.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let (t, u, v) = intersect(triangle().vertices, ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!((t, u, v), (1.0, 0.0, 1.0));
+	}
+}
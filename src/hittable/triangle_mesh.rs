@@ -0,0 +1,345 @@
+//! Meshes of triangles sharing their vertices, which is how most models are stored.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Ray, Vec3};
+use crate::hittable::{triangle, Aabb, Bvh, Hit, Hittable};
+use crate::material::Material;
+
+/// Indexed triangle mesh: Every triangle references three vertices in buffers shared by the whole mesh.
+///
+/// Each vertex has a position and optionally a normal and texture coordinates. With vertex normals, the normal of
+/// a hit is interpolated between the normals of the triangle's vertices, so that curved surfaces look smooth
+/// despite being made of flat triangles. Triangles with a zero-length vertex normal are shaded flat instead. The mesh
+/// builds its own [Bvh] over its triangles.
+pub struct TriangleMesh {
+	data: Arc<MeshData>,
+	bvh: Arc<Bvh>,
+	/// Material used instead of the one the mesh was created with, see [TriangleMesh::with_material].
+	material: Option<Arc<dyn Material>>,
+}
+
+struct MeshData {
+	positions: Vec<Vec3>,
+	normals: Vec<Vec3>,
+	uvs: Vec<[f64; 2]>,
+	triangles: Vec<[usize; 3]>,
+	material: Arc<dyn Material>,
+}
+
+/// A single triangle of a [TriangleMesh], as stored in the mesh's [Bvh].
+struct MeshTriangle {
+	data: Arc<MeshData>,
+	index: usize,
+}
+
+/// Reason why the buffers passed to [TriangleMesh::new] don't form a valid mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MeshError {
+	/// There are vertex normals, but not one for every vertex.
+	NormalCount { positions: usize, normals: usize },
+	/// There are texture coordinates, but not one pair for every vertex.
+	UvCount { positions: usize, uvs: usize },
+	/// A triangle references a vertex that doesn't exist.
+	VertexIndex { triangle: usize, index: usize },
+}
+
+impl fmt::Display for MeshError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			MeshError::NormalCount { positions, normals } => {
+				write!(f, "{} normals given for {} vertices", normals, positions)
+			}
+			MeshError::UvCount { positions, uvs } => {
+				write!(f, "{} texture coordinates given for {} vertices", uvs, positions)
+			}
+			MeshError::VertexIndex { triangle, index } => {
+				write!(f, "triangle {} references vertex {}, which doesn't exist", triangle, index)
+			}
+		}
+	}
+}
+
+impl Error for MeshError {}
+
+impl TriangleMesh {
+	/// Creates a mesh from its vertex buffers and the vertex indices of every triangle.
+	///
+	/// `normals` and `uvs` are either empty or contain one entry per position. Triangles are front facing where
+	/// their vertices appear in counter-clockwise order.
+	pub fn new(
+		positions: Vec<Vec3>,
+		normals: Vec<Vec3>,
+		uvs: Vec<[f64; 2]>,
+		triangles: Vec<[usize; 3]>,
+		material: Arc<dyn Material>,
+	) -> Result<Self, MeshError> {
+		if !normals.is_empty() && normals.len() != positions.len() {
+			return Err(MeshError::NormalCount { positions: positions.len(), normals: normals.len() });
+		}
+		if !uvs.is_empty() && uvs.len() != positions.len() {
+			return Err(MeshError::UvCount { positions: positions.len(), uvs: uvs.len() });
+		}
+		for (triangle, indices) in triangles.iter().enumerate() {
+			if let Some(&index) = indices.iter().find(|&&index| index >= positions.len()) {
+				return Err(MeshError::VertexIndex { triangle, index });
+			}
+		}
+
+		let data = Arc::new(MeshData {
+			// Normals are interpolated, so they have to be normalized. Those without a direction are kept as zero.
+			normals: normals.iter()
+				.map(|normal| if normal.is_near_zero() { Vec3::ZERO } else { normal.unit_vector() })
+				.collect(),
+			positions,
+			uvs,
+			triangles,
+			material,
+		});
+		let bvh = Bvh::new(
+			(0..data.triangles.len())
+				.map(|index| Box::new(MeshTriangle { data: Arc::clone(&data), index }) as Box<dyn Hittable>)
+				.collect(),
+		);
+		Ok(Self { data, bvh: Arc::new(bvh), material: None })
+	}
+
+	/// Returns a copy of the mesh using `material` for all triangles. The copy shares its vertex buffers and [Bvh]
+	/// with this mesh.
+	pub fn with_material(&self, material: Arc<dyn Material>) -> TriangleMesh {
+		Self { data: Arc::clone(&self.data), bvh: Arc::clone(&self.bvh), material: Some(material) }
+	}
+
+	/// Returns the material of all triangles.
+	pub fn material(&self) -> &Arc<dyn Material> {
+		self.material.as_ref().unwrap_or(&self.data.material)
+	}
+
+	/// Returns the position of every vertex.
+	pub fn positions(&self) -> &[Vec3] {
+		&self.data.positions
+	}
+
+	/// Returns the unit normal of every vertex, or an empty slice if the mesh has no vertex normals. Normals given
+	/// with zero length stay zero.
+	pub fn normals(&self) -> &[Vec3] {
+		&self.data.normals
+	}
+
+	/// Returns the texture coordinates of every vertex, or an empty slice if the mesh has none.
+	pub fn uvs(&self) -> &[[f64; 2]] {
+		&self.data.uvs
+	}
+
+	/// Returns the vertex indices of every triangle.
+	pub fn triangles(&self) -> &[[usize; 3]] {
+		&self.data.triangles
+	}
+}
+
+impl Hittable for TriangleMesh {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let hit = self.bvh.hits(ray, t_min, t_max)?;
+		match &self.material {
+			Some(material) => Some(Hit { material: Arc::clone(material), ..hit }),
+			None => Some(hit),
+		}
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		self.bvh.bounding_box()
+	}
+}
+
+impl fmt::Debug for TriangleMesh {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TriangleMesh")
+			.field("vertices", &self.data.positions.len())
+			.field("triangles", &self.data.triangles.len())
+			.field("material", self.material())
+			.finish()
+	}
+}
+
+impl MeshTriangle {
+	fn vertices(&self) -> [Vec3; 3] {
+		self.data.triangles[self.index].map(|index| self.data.positions[index])
+	}
+}
+
+impl Hittable for MeshTriangle {
+	fn hits(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+		let vertices = self.vertices();
+		let (t, u, v) = triangle::intersect(vertices, ray, t_min, t_max)?;
+
+		let [v0, v1, v2] = vertices;
+		let indices = self.data.triangles[self.index];
+		let weights = [1.0 - u - v, u, v];
+		let geometric_normal = (v1 - v0).cross(v2 - v0).unit_vector();
+		let shading_normal = if self.data.normals.is_empty() {
+			geometric_normal
+		} else {
+			let [n0, n1, n2] = indices.map(|index| self.data.normals[index]);
+			let normal = n0 * weights[0] + n1 * weights[1] + n2 * weights[2];
+			// Without a normal at every vertex, or where opposite normals cancel out, the triangle is shaded flat
+			if [n0, n1, n2].contains(&Vec3::ZERO) || normal.is_near_zero() {
+				geometric_normal
+			} else {
+				normal.unit_vector()
+			}
+		};
+		let uv = if self.data.uvs.is_empty() {
+			[u, v]
+		} else {
+			let interpolate = |component: usize| {
+				(0..3).map(|vertex| self.data.uvs[indices[vertex]][component] * weights[vertex]).sum()
+			};
+			[interpolate(0), interpolate(1)]
+		};
+		Some(triangle::hit(ray, t, uv, geometric_normal, shading_normal, &self.data.material))
+	}
+
+	fn bounding_box(&self) -> Option<Aabb> {
+		Some(triangle::bounding_box(self.vertices()))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::material;
+
+	use super::*;
+
+	/// Square in the xy plane made of two triangles, with normals tilted outwards like on a dome.
+	fn square(normals: Vec<Vec3>) -> Result<TriangleMesh, MeshError> {
+		TriangleMesh::new(
+			vec![
+				Vec3 { x: -1.0, y: -1.0, z: 0.0 },
+				Vec3 { x: 1.0, y: -1.0, z: 0.0 },
+				Vec3 { x: 1.0, y: 1.0, z: 0.0 },
+				Vec3 { x: -1.0, y: 1.0, z: 0.0 },
+			],
+			normals,
+			vec![],
+			vec![[0, 1, 2], [0, 2, 3]],
+			Arc::new(material::Lambertian::default()),
+		)
+	}
+
+	fn dome_normals() -> Vec<Vec3> {
+		vec![
+			Vec3 { x: -1.0, y: -1.0, z: 1.0 },
+			Vec3 { x: 1.0, y: -1.0, z: 1.0 },
+			Vec3 { x: 1.0, y: 1.0, z: 1.0 },
+			Vec3 { x: -1.0, y: 1.0, z: 1.0 },
+		]
+	}
+
+	#[test]
+	fn hits() {
+		let mesh = square(vec![]).unwrap();
+		let ray = Ray {
+			origin: Vec3 { x: -0.5, y: 0.5, z: 2.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let hit = mesh.hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!(hit.t, 2.0);
+		assert_eq!(hit.normal, Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+
+		let beside = Ray { origin: Vec3 { x: 1.5, y: 0.0, z: 2.0 }, ..ray };
+		assert!(mesh.hits(beside, 0.0, f64::INFINITY).is_none());
+		assert_eq!(mesh.bounding_box().unwrap().extent(), Vec3 { x: 2.0, y: 2.0, z: 0.0 });
+	}
+
+	#[test]
+	fn interpolated_normals() {
+		let mesh = square(dome_normals()).unwrap();
+		let ray = |x, y| Ray {
+			origin: Vec3 { x, y, z: 2.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		// In the center, the tilted normals cancel out
+		let normal = mesh.hits(ray(0.0, 0.0), 0.0, f64::INFINITY).unwrap().normal;
+		assert!((normal - Vec3 { x: 0.0, y: 0.0, z: 1.0 }).length() < 1e-12);
+		// Towards a corner, the normal leans towards it
+		let normal = mesh.hits(ray(0.9, 0.9), 0.0, f64::INFINITY).unwrap().normal;
+		assert!(normal.x > 0.5 && normal.y > 0.5);
+		// Seen from behind, the normal is flipped
+		let behind = Ray {
+			origin: Vec3 { x: 0.9, y: 0.9, z: -2.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			time: 0.0,
+		};
+		let normal = mesh.hits(behind, 0.0, f64::INFINITY).unwrap().normal;
+		assert!(normal.x < -0.5 && normal.z < 0.0);
+	}
+
+	#[test]
+	fn zero_normals() {
+		let mut normals = dome_normals();
+		normals[2] = Vec3::ZERO;
+		let mesh = square(normals).unwrap();
+		assert_eq!(mesh.normals()[2], Vec3::ZERO);
+		let ray = |x, y| Ray {
+			origin: Vec3 { x, y, z: 2.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		// Both triangles share the vertex without a normal, so they are shaded flat
+		for (x, y) in [(0.9, 0.5), (-0.5, 0.9)] {
+			assert_eq!(mesh.hits(ray(x, y), 0.0, f64::INFINITY).unwrap().normal, Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+		}
+	}
+
+	#[test]
+	fn interpolated_uvs() {
+		let square = square(vec![]).unwrap();
+		let uvs = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+		let mesh = TriangleMesh::new(
+			square.positions().to_vec(),
+			vec![],
+			uvs,
+			square.triangles().to_vec(),
+			Arc::new(material::Lambertian::default()),
+		).unwrap();
+		let ray = Ray {
+			origin: Vec3 { x: 0.5, y: -0.5, z: 2.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		assert_eq!(mesh.hits(ray, 0.0, f64::INFINITY).unwrap().uv, [0.75, 0.25]);
+	}
+
+	#[test]
+	fn with_material() {
+		let mesh = square(dome_normals()).unwrap();
+		let light: Arc<dyn Material> = Arc::new(material::DiffuseLight::default());
+		let lamp = mesh.with_material(Arc::clone(&light));
+		assert!(Arc::ptr_eq(&lamp.data, &mesh.data) && Arc::ptr_eq(&lamp.bvh, &mesh.bvh));
+		assert!(Arc::ptr_eq(lamp.material(), &light));
+
+		let ray = Ray {
+			origin: Vec3 { x: 0.5, y: 0.5, z: 2.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		assert!(Arc::ptr_eq(&lamp.hits(ray, 0.0, f64::INFINITY).unwrap().material, &light));
+		assert!(!Arc::ptr_eq(&mesh.hits(ray, 0.0, f64::INFINITY).unwrap().material, &light));
+	}
+
+	#[test]
+	fn invalid_buffers() {
+		let normals = vec![Vec3 { x: 0.0, y: 0.0, z: 1.0 }];
+		assert_eq!(square(normals).unwrap_err(), MeshError::NormalCount { positions: 4, normals: 1 });
+
+		let material = Arc::new(material::Lambertian::default());
+		let error = TriangleMesh::new(vec![Vec3::default(); 3], vec![], vec![], vec![[0, 1, 3]], material).unwrap_err();
+		assert_eq!(error, MeshError::VertexIndex { triangle: 0, index: 3 });
+		assert_eq!(error.to_string(), "triangle 0 references vertex 3, which doesn't exist");
+	}
+}
@@ -1,7 +1,19 @@
+use std::fs;
 use std::io;
+use std::path::Path;
 
 use crate::color::Color;
 
+/// Channel layout of 8-bit PNG files written by [Image::write_png].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PngFormat {
+	/// Red, green and blue.
+	#[default]
+	Rgb,
+	/// Red, green, blue and alpha. All pixels are fully opaque, since the image has no transparency.
+	Rgba,
+}
+
 /// Image containing rows of pixels.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Image {
@@ -18,7 +30,7 @@ impl Image {
 
 	/// Returns the width of the image.
 	pub fn width(&self) -> usize {
-		match self.rows.get(0) {
+		match self.rows.first() {
 			Some(row) => row.len(),
 			None => 0,
 		}
@@ -29,6 +41,26 @@ impl Image {
 		self.rows.len()
 	}
 
+	/// Loads the Portable Pixmap (PPM) or Portable Network Graphics (PNG) image at `path`.
+	pub fn load(path: impl AsRef<Path>) -> io::Result<Image> {
+		Self::decode(&fs::read(path)?)
+	}
+
+	/// Decodes a Portable Pixmap (PPM, type P3 or P6) or Portable Network Graphics (PNG) image. The format is
+	/// detected from the first bytes.
+	///
+	/// PNG images of any color type are converted to 8-bit RGB, dropping the alpha channel. PPM images with a
+	/// maximum value other than 255 are scaled to 8 bits.
+	pub fn decode(data: &[u8]) -> io::Result<Image> {
+		if data.starts_with(b"\x89PNG") {
+			decode_png(data)
+		} else if data.starts_with(b"P3") || data.starts_with(b"P6") {
+			decode_ppm(data)
+		} else {
+			Err(invalid_data("Unknown image format, expected PPM (P3 or P6) or PNG".to_string()))
+		}
+	}
+
 	/// Returns the aspect ratio of the image as float.
 	pub fn aspect_ratio(&self) -> f64 {
 		self.width() as f64 / self.height() as f64
@@ -49,6 +81,21 @@ impl Image {
 		Ok(())
 	}
 
+	/// Writes the image as 8-bit Portable Network Graphics (PNG) with the channels of `format` to the given writer.
+	pub fn write_png(&self, writer: &mut impl io::Write, format: PngFormat) -> io::Result<()> {
+		let (color_type, data) = match format {
+			PngFormat::Rgb => (
+				png::ColorType::Rgb,
+				self.rows.iter().flatten().flat_map(|pixel| [pixel.r, pixel.g, pixel.b]).collect::<Vec<u8>>(),
+			),
+			PngFormat::Rgba => (
+				png::ColorType::Rgba,
+				self.rows.iter().flatten().flat_map(|pixel| [pixel.r, pixel.g, pixel.b, u8::MAX]).collect(),
+			),
+		};
+		write_png(writer, self.width(), self.height(), color_type, png::BitDepth::Eight, &data)
+	}
+
 	/// Writes the image as ASCII Portable Pixmap (PPM, type P3) to the given writer.
 	pub fn write_ascii_ppm(&self, writer: &mut impl io::Write) -> io::Result<()> {
 		// Header
@@ -65,4 +112,194 @@ impl Image {
 
 		Ok(())
 	}
-}
\ No newline at end of file
+}
+
+fn invalid_data(message: String) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn decode_png(data: &[u8]) -> io::Result<Image> {
+	let mut decoder = png::Decoder::new(io::Cursor::new(data));
+	decoder.set_transformations(png::Transformations::normalize_to_color8());
+	let mut reader = decoder.read_info()?;
+	let mut buffer = vec![0; reader.output_buffer_size().ok_or_else(|| invalid_data("PNG is too large".to_string()))?];
+	let info = reader.next_frame(&mut buffer)?;
+	let (color_type, _) = reader.output_color_type();
+
+	let channels = color_type.samples();
+	let pixel = |samples: &[u8]| match color_type {
+		png::ColorType::Grayscale | png::ColorType::GrayscaleAlpha => {
+			Color { r: samples[0], g: samples[0], b: samples[0] }
+		}
+		_ => Color { r: samples[0], g: samples[1], b: samples[2] },
+	};
+	let rows = buffer[..info.buffer_size()]
+		.chunks_exact(info.line_size)
+		.map(|line| line[..info.width as usize * channels].chunks_exact(channels).map(pixel).collect())
+		.collect();
+	Ok(Image { rows })
+}
+
+fn decode_ppm(data: &[u8]) -> io::Result<Image> {
+	let binary = data.starts_with(b"P6");
+	// Position after the magic number
+	let mut position = 2;
+	// Reads the next whitespace separated token, skipping comments
+	let mut token = || {
+		loop {
+			match data.get(position) {
+				Some(b'#') => {
+					while data.get(position).is_some_and(|&byte| byte != b'\n') {
+						position += 1;
+					}
+				}
+				Some(byte) if byte.is_ascii_whitespace() => position += 1,
+				_ => break,
+			}
+		}
+		let start = position;
+		while data.get(position).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+			position += 1;
+		}
+		let token = std::str::from_utf8(&data[start..position]).unwrap_or("");
+		(token, position)
+	};
+	let mut number = |name: &str| {
+		let (token, end) = token();
+		token.parse::<usize>()
+			.map(|value| (value, end))
+			.map_err(|_| invalid_data(format!("Invalid PPM {} `{}`", name, token)))
+	};
+
+	let (width, _) = number("width")?;
+	let (height, _) = number("height")?;
+	if width == 0 || height == 0 {
+		return Err(invalid_data(format!("Invalid PPM size {}x{}", width, height)));
+	}
+	let (max_value, header_end) = number("maximum value")?;
+	if !(1..=u16::MAX as usize).contains(&max_value) {
+		return Err(invalid_data(format!("Invalid PPM maximum value {}", max_value)));
+	}
+	let sample_count = width.checked_mul(height).and_then(|count| count.checked_mul(3))
+		.ok_or_else(|| invalid_data("PPM is too large".to_string()))?;
+
+	let samples: Vec<usize> = if binary {
+		// A single whitespace character separates the header from the samples, which are big endian with two
+		// bytes if the maximum value needs them
+		let bytes_per_sample = if max_value > u8::MAX as usize { 2 } else { 1 };
+		let start = header_end + 1;
+		let end = sample_count.checked_mul(bytes_per_sample).and_then(|size| size.checked_add(start));
+		let bytes = end.and_then(|end| data.get(start..end))
+			.ok_or_else(|| invalid_data("PPM ends before all pixels are read".to_string()))?;
+		bytes.chunks_exact(bytes_per_sample)
+			.map(|sample| sample.iter().fold(0, |value, &byte| value << 8 | byte as usize))
+			.collect()
+	} else {
+		(0..sample_count)
+			.map(|_| number("sample").map(|(value, _)| value))
+			.collect::<io::Result<_>>()?
+	};
+	if let Some(sample) = samples.iter().find(|&&sample| sample > max_value) {
+		return Err(invalid_data(format!("PPM sample {} exceeds the maximum value {}", sample, max_value)));
+	}
+
+	let scale = |sample: usize| ((sample * u8::MAX as usize + max_value / 2) / max_value) as u8;
+	let rows = samples.chunks_exact(3 * width)
+		.map(|row| row.chunks_exact(3).map(|rgb| Color { r: scale(rgb[0]), g: scale(rgb[1]), b: scale(rgb[2]) }).collect())
+		.collect();
+	Ok(Image { rows })
+}
+
+/// Encodes the raw pixel `data` (rows from top to bottom, big endian for 16-bit depth) as PNG.
+pub(crate) fn write_png(
+	writer: &mut impl io::Write,
+	width: usize,
+	height: usize,
+	color_type: png::ColorType,
+	bit_depth: png::BitDepth,
+	data: &[u8],
+) -> io::Result<()> {
+	let dimension = |value: usize| u32::try_from(value)
+		.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Image is too large for PNG"));
+	let mut encoder = png::Encoder::new(writer, dimension(width)?, dimension(height)?);
+	encoder.set_color(color_type);
+	encoder.set_depth(bit_depth);
+	let mut png_writer = encoder.write_header()?;
+	png_writer.write_image_data(data)?;
+	png_writer.finish()?;
+	Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn image() -> Image {
+		let mut image = Image::new(3, 2);
+		image.rows[0][1] = Color { r: 255, g: 128, b: 0 };
+		image
+	}
+
+	#[test]
+	fn binary_ppm() {
+		let mut ppm = Vec::new();
+		image().write_binary_ppm(&mut ppm).unwrap();
+		assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+		assert_eq!(ppm.len(), 11 + 3 * 2 * 3);
+	}
+
+	#[test]
+	fn decode_ppm() {
+		for write in [Image::write_binary_ppm, Image::write_ascii_ppm] {
+			let mut ppm = Vec::new();
+			write(&image(), &mut ppm).unwrap();
+			assert_eq!(Image::decode(&ppm).unwrap(), image());
+		}
+
+		let ascii = b"P3\n# A comment\n2 1 # another one\n1000\n1000 0 500  0 0 0\n";
+		let image = Image::decode(ascii).unwrap();
+		assert_eq!(image.rows, vec![vec![Color { r: 255, g: 0, b: 128 }, Color { r: 0, g: 0, b: 0 }]]);
+
+		let sixteen_bit = b"P6 1 1 65535\n\xff\xff\x00\x00\x80\x00";
+		assert_eq!(Image::decode(sixteen_bit).unwrap().rows[0][0], Color { r: 255, g: 0, b: 128 });
+
+		assert!(Image::decode(b"P6 2 2 255\n\x00\x00").is_err());
+		for empty in [&b"P3 0 2 255\n"[..], b"P6 2 0 255\n", b"P3 0 0 255\n"] {
+			assert_eq!(Image::decode(empty).unwrap_err().kind(), io::ErrorKind::InvalidData);
+		}
+		assert!(Image::decode(b"P3 1 1 255 0 0 256").is_err());
+		assert!(Image::decode(b"GIF89a").is_err());
+	}
+
+	#[test]
+	fn decode_png() {
+		for format in [PngFormat::Rgb, PngFormat::Rgba] {
+			let mut png = Vec::new();
+			image().write_png(&mut png, format).unwrap();
+			assert_eq!(Image::decode(&png).unwrap(), image());
+		}
+
+		let mut gray = Vec::new();
+		write_png(&mut gray, 2, 1, png::ColorType::Grayscale, png::BitDepth::Sixteen, &[0xff, 0xff, 0x40, 0x00]).unwrap();
+		let gray = Image::decode(&gray).unwrap();
+		assert_eq!(gray.rows[0], vec![Color { r: 255, g: 255, b: 255 }, Color { r: 64, g: 64, b: 64 }]);
+	}
+
+	#[test]
+	fn png() {
+		for (format, channels) in [(PngFormat::Rgb, 3), (PngFormat::Rgba, 4)] {
+			let mut png = Vec::new();
+			image().write_png(&mut png, format).unwrap();
+
+			let mut reader = png::Decoder::new(io::Cursor::new(png)).read_info().unwrap();
+			let mut data = vec![0; reader.output_buffer_size().unwrap()];
+			let info = reader.next_frame(&mut data).unwrap();
+			assert_eq!((info.width, info.height, info.bit_depth), (3, 2, png::BitDepth::Eight));
+			assert_eq!(&data[channels..channels + 3], &[255, 128, 0]);
+			if format == PngFormat::Rgba {
+				assert_eq!(data[3], u8::MAX);
+			}
+		}
+	}
+}
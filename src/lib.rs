@@ -1,17 +1,31 @@
 // #![allow(dead_code)]
 
-pub use camera::Camera;
+pub use camera::{Camera, CameraBuilder, CameraError, Projection};
 pub use color::Color;
-pub use image::Image;
+pub use framebuffer::Framebuffer;
+pub use image::{Image, PngFormat};
 pub use ray::Ray;
+pub use rgb::{Rgb, TransferFunction};
+pub use renderer::{Background, Fog, Renderer, RenderSettings, RenderStatistics};
+pub use scene::Scene;
+pub use transform::Transform;
 pub use vec3::Vec3;
 
 mod image;
 mod vec3;
+mod transform;
 mod color;
+mod rgb;
+mod framebuffer;
 pub mod hittable;
 pub mod ray;
 mod camera;
 pub mod material;
+pub mod random;
+pub mod texture;
+pub mod exr;
+pub mod obj;
+mod renderer;
+pub mod scene;
 
 
@@ -1,179 +1,296 @@
 use std::error::Error;
 use std::fs::File;
-use std::io::BufWriter;
-use std::rc::Rc;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
 
-use rand::Rng;
+use clap::{Parser, ValueEnum};
 
-use raytracer::{Camera, Color};
-use raytracer::hittable::{Hittable, Sphere};
-use raytracer::Image;
+use raytracer::{exr, Camera, Framebuffer, PngFormat, RenderSettings, Renderer, Rgb, Scene, TransferFunction};
+use raytracer::hittable::{Bvh, Hittable, Plane, Sphere};
 use raytracer::material::{Dielectric, Lambertian, Material, Metal};
-use raytracer::Ray;
 use raytracer::Vec3;
 
-fn main() -> Result<(), Box<dyn Error>> {
-	// Image
-	let aspect_ratio = 16.0 / 9.0;
-	let image_width = 480 /*px*/;
-	let image_height = (image_width as f64 / aspect_ratio) as usize;
-	let antialiasing_samples_per_pixel = 30;
-	let reflection_depth = 30;
-	let mut image = Image::new(image_width, image_height);
-	dbg!(aspect_ratio, image_width, image_height);
-
-	// World
-	let world: Vec<Box<dyn Hittable>> = {
-		let material_ground: Rc<dyn Material> = Rc::new(Lambertian {
-			albedo: Color::from(Vec3 { x: 0.5, y: 0.5, z: 0.5 })
-		});
-		let material_center: Rc<dyn Material> = Rc::new(Lambertian {
-			albedo: Color::from(Vec3 { x: 0.7, y: 0.3, z: 0.3 })
-		});
-		let material_left: Rc<dyn Material> = Rc::new(Dielectric {
-			index_of_refraction: -0.4,
-		});
-		let material_right: Rc<dyn Material> = Rc::new(Metal {
-			albedo: Color::from(Vec3 { x: 0.8, y: 0.6, z: 0.2 }),
-			fuzziness: 0.3,
-		});
+/// Renders a scene by ray tracing and writes the image to a file.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+	/// Scene description as JSON. Renders a built-in demo scene if omitted.
+	scene: Option<PathBuf>,
 
-		vec![
-			Box::new(Sphere {
-				center: Vec3 { x: 0.0, y: -100.5, z: -1.0 },
-				radius: 100.0,
-				material: Rc::clone(&material_ground),
-			}),
-			Box::new(Sphere {
-				center: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
-				radius: 0.5,
-				material: Rc::clone(&material_center),
-			}),
-			Box::new(Sphere {
-				center: Vec3 { x: -1.0, y: 0.0, z: -1.0 },
-				radius: 0.5,
-				material: Rc::clone(&material_left),
-			}),
-			Box::new(Sphere {
-				center: Vec3 { x: -0.3, y: -0.3, z: -0.5 },
-				radius: 0.2,
-				material: Rc::clone(&material_right),
-			}),
-		]
-	};
+	/// Image width in pixels [default: from the scene, or derived from --height]
+	#[arg(long, value_parser = parse_positive)]
+	width: Option<usize>,
+
+	/// Image height in pixels [default: from the scene, or derived from --width]
+	#[arg(long, value_parser = parse_positive)]
+	height: Option<usize>,
+
+	/// Number of antialiasing samples per pixel [default: from the scene]
+	#[arg(short, long, value_parser = parse_positive)]
+	samples: Option<usize>,
+
+	/// Maximum number of times a ray is scattered [default: from the scene]
+	#[arg(short = 'd', long, value_parser = parse_positive)]
+	max_depth: Option<usize>,
+
+	/// Path of the output image
+	#[arg(short, long, default_value = "image.ppm")]
+	output: PathBuf,
+
+	/// Format of the output image [default: from the extension of the output path]
+	#[arg(short, long, value_enum)]
+	format: Option<Format>,
+
+	/// Transfer function encoding the linear radiance into 8- or 16-bit pixel values. Ignored by the high dynamic
+	/// range formats, which store linear radiance.
+	#[arg(short, long, value_enum, default_value_t = Transfer::Gamma2)]
+	transfer: Transfer,
+
+	/// Seed for the random samples, to render the same image every time [default: random]
+	#[arg(long)]
+	seed: Option<u64>,
+
+	/// Number of render threads [default: number of CPU cores]
+	#[arg(short = 'j', long, value_parser = parse_positive)]
+	threads: Option<usize>,
+}
+
+/// Image file formats.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Format {
+	/// Binary Portable Pixmap (P6)
+	Ppm,
+	/// ASCII Portable Pixmap (P3)
+	PpmAscii,
+	/// 8-bit RGB Portable Network Graphics
+	Png,
+	/// 8-bit RGBA Portable Network Graphics
+	PngRgba,
+	/// 16-bit RGB Portable Network Graphics
+	Png16,
+	/// OpenEXR with linear radiance as 16-bit floats
+	Exr,
+	/// OpenEXR with linear radiance as 32-bit floats
+	ExrFloat,
+	/// Radiance HDR (RGBE) with linear radiance
+	Hdr,
+	/// Portable Float Map with linear radiance
+	Pfm,
+}
+
+impl Format {
+	/// Determines the format from the extension of the `path`.
+	fn from_extension(path: &Path) -> Option<Format> {
+		match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+			"ppm" => Some(Format::Ppm),
+			"png" => Some(Format::Png),
+			"exr" => Some(Format::Exr),
+			"hdr" => Some(Format::Hdr),
+			"pfm" => Some(Format::Pfm),
+			_ => None,
+		}
+	}
+}
+
+/// Transfer functions from linear radiance to 8-bit pixel values.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Transfer {
+	/// Gamma 2, i.e. the square root
+	Gamma2,
+	/// Standard sRGB curve
+	Srgb,
+	/// No encoding, the radiance is clamped to 0.0..=1.0 and stored as is
+	Linear,
+}
+
+impl From<Transfer> for TransferFunction {
+	fn from(transfer: Transfer) -> Self {
+		match transfer {
+			Transfer::Gamma2 => TransferFunction::Gamma(2.0),
+			Transfer::Srgb => TransferFunction::Srgb,
+			Transfer::Linear => TransferFunction::Linear,
+		}
+	}
+}
+
+/// Parses an integer greater than zero.
+fn parse_positive(value: &str) -> Result<usize, String> {
+	match value.parse::<usize>() {
+		Ok(0) => Err("must be at least 1".to_string()),
+		Ok(number) => Ok(number),
+		Err(error) => Err(error.to_string()),
+	}
+}
+
+fn main() -> ExitCode {
+	let args = Args::parse();
+	match run(&args) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(error) => {
+			eprintln!("error: {}", error);
+			ExitCode::FAILURE
+		}
+	}
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+	let format = args.format
+		.or_else(|| Format::from_extension(&args.output))
+		.ok_or_else(|| format!(
+			"cannot determine the image format from the extension of `{}`, use --format",
+			args.output.display(),
+		))?;
 
-	// Camera
-	let camera = Camera::default();
-	let viewport_height = 2.0;
-	let viewport_width = aspect_ratio * viewport_height;
-	let focal_length = 1.0;
-	dbg!(viewport_width, viewport_height, focal_length);
-
-	// Eye or camera center
-	// Offset vectors from the lower upper left corner of the viewport
-	let horizontal = Vec3 { x: viewport_width, y: 0.0, z: 0.0 };
-	let vertical = Vec3 { x: 0.0, y: -viewport_height, z: 0.0 };
-	// "Origin" of the viewport
-	let upper_left_corner = {
-		// TODO: I would prefer to use the positive z-axis, but I'll follow the tutorial for now.
-		//  "In order to respect the convention of a right handed coordinate system, into the screen is the negative z-axis."
-		let viewport_center = camera.look_from - Vec3 { x: 0.0, y: 0.0, z: focal_length };
-		viewport_center
-			- horizontal / 2.0 // half a screen to the left
-			- vertical / 2.0 // half a screen to the top
+	let mut scene = match &args.scene {
+		Some(path) => Scene::load(path)?,
+		None => demo_scene(),
 	};
-	dbg!(upper_left_corner);
+	apply_args(&mut scene, args);
+	let settings = &scene.settings;
+	println!("Image size {}x{}, {} samples per pixel", settings.width, settings.height, settings.samples_per_pixel);
 
 	println!("Rendering image");
 	let render_start_timestamp = std::time::Instant::now();
-	// Render
-	for (y, row) in image.rows.iter_mut().enumerate() {
-		for (x, pixel) in row.iter_mut().enumerate() {
-			// Antialiasing
-			let mut color_vec = Vec3::default();
-			for _sample in 0..antialiasing_samples_per_pixel {
-				// Offset vectors from the lower upper left corner into the pixel of the viewport
-				let horizontal_scalar = random_scalar_sample(x, image_width);
-				let vertical_scalar = random_scalar_sample(y, image_height);
-				let ray = camera.ray(horizontal_scalar, vertical_scalar);
-				color_vec = color_vec + Vec3::from(ray_color(ray, &world.as_slice(), reflection_depth));
+	let image_height = settings.height;
+	let (framebuffer, statistics) = Renderer::new(scene.settings.clone())
+		.render_with_statistics(&scene.camera, &scene.world, |rendered_rows| {
+			if rendered_rows % 16 == 0 || rendered_rows == image_height {
+				println!("{}%", rendered_rows * 100 / image_height);
 			}
-			color_vec = color_vec / antialiasing_samples_per_pixel as f64;
-			*pixel = Color::from(color_vec).gamma_corrected();
-		}
-		if y & 0xf == 0 {
-			println!("{}%", y * 100 / image_height);
-		}
-	}
+		});
 	let render_duration = render_start_timestamp.elapsed();
 	println!("Rendered image in {:?}", render_duration);
+	let percentage = |count: u64| count as f64 * 100.0 / statistics.paths.max(1) as f64;
+	println!(
+		"Traced {} paths with {:.2} rays on average: {:.1}% escaped, {:.1}% absorbed, {:.1}% terminated by Russian \
+		roulette, {:.1}% cut off at the maximum depth",
+		statistics.paths,
+		statistics.average_path_length(),
+		percentage(statistics.escaped),
+		percentage(statistics.absorbed),
+		percentage(statistics.terminated),
+		percentage(statistics.max_depth_reached),
+	);
 
-	println!("Writing image to filesystem");
+	println!("Writing image to {}", args.output.display());
 	let write_start_timestamp = std::time::Instant::now();
-	let file = File::create("image.ppm")?;
-	let mut file_writer = BufWriter::new(file);
-	image.write_binary_ppm(&mut file_writer)?;
+	let file = File::create(&args.output)?;
+	let mut writer = BufWriter::new(file);
+	write_image(&framebuffer, format, args.transfer.into(), &mut writer)?;
+	// Dropping the writer would flush it as well, but ignore errors
+	writer.flush()?;
 	let write_duration = write_start_timestamp.elapsed();
 	println!("Wrote image to filesystem in {:?}", write_duration);
 
 	Ok(())
 }
 
-
-/// Random anti-aliasing sample
-fn random_scalar_sample(iteration: usize, dimension: usize) -> f64 {
-	// TODO: Deterministic samples
-	assert!(iteration < dimension);
-	// It's important that the random_scalar_offset goes with the iteration, not the output pixel f64, since
-	// this is not scaled by the viewport width or height.
-	let random_scalar_offset: f64 = rand::thread_rng().gen_range(0.0..1.0);
-	(iteration as f64 + random_scalar_offset) / (dimension as f64 - 1.0)
+/// Writes the `framebuffer` in the `format`, encoding its linear radiance with the `transfer` function unless the
+/// format stores linear radiance.
+fn write_image(
+	framebuffer: &Framebuffer,
+	format: Format,
+	transfer: TransferFunction,
+	writer: &mut impl io::Write,
+) -> io::Result<()> {
+	match format {
+		Format::Ppm => framebuffer.to_image(transfer).write_binary_ppm(writer),
+		Format::PpmAscii => framebuffer.to_image(transfer).write_ascii_ppm(writer),
+		Format::Png => framebuffer.to_image(transfer).write_png(writer, PngFormat::Rgb),
+		Format::PngRgba => framebuffer.to_image(transfer).write_png(writer, PngFormat::Rgba),
+		Format::Png16 => framebuffer.write_png16(writer, transfer),
+		Format::Exr => framebuffer.write_exr(writer, exr::SampleType::Half),
+		Format::ExrFloat => framebuffer.write_exr(writer, exr::SampleType::Float),
+		Format::Hdr => framebuffer.write_radiance_hdr(writer),
+		Format::Pfm => framebuffer.write_pfm(writer),
+	}
 }
 
+/// Overrides the settings of the `scene` with the ones given on the command line.
+fn apply_args(scene: &mut Scene, args: &Args) {
+	let settings = &mut scene.settings;
+	let aspect_ratio = settings.width as f64 / settings.height as f64;
+	// If only one dimension is given, the other one keeps the aspect ratio of the scene
+	let (width, height) = match (args.width, args.height) {
+		(Some(width), Some(height)) => (width, height),
+		(Some(width), None) => (width, usize::max((width as f64 / aspect_ratio) as usize, 1)),
+		(None, Some(height)) => (usize::max((height as f64 * aspect_ratio) as usize, 1), height),
+		(None, None) => (settings.width, settings.height),
+	};
+	settings.width = width;
+	settings.height = height;
+	scene.camera = scene.camera.with_aspect_ratio(width as f64 / height as f64)
+		.expect("The aspect ratio of positive dimensions is positive");
 
-/// Calculates the color for the `ray` by tracing it through the `world`.
-/// The `depth` parameter limits the recursion depth of reflection rays.
-fn ray_color(ray: Ray, world: &dyn Hittable, depth: usize) -> Color {
-	if depth == 0 {
-		// Exceeded the ray bounce limit; no more light is gathered.
-		return Color::default();
+	if let Some(samples) = args.samples {
+		settings.samples_per_pixel = samples;
 	}
-
-	// Hit something on the world?
-	if let Some(hit) = world.hits(ray, /*against shadow acne*/0.0001, f64::INFINITY) {
-		return Rc::clone(&hit.material).scatter(ray, hit)
-			.map(|(scattered, attenuation)| {
-				let attenuation_vec = Vec3::from(attenuation);
-				let scattered_color_vec = Vec3::from(ray_color(scattered, world, depth - 1));
-				Color::from(attenuation_vec * scattered_color_vec)
-			})
-			.unwrap_or(Color::default());
+	if let Some(max_depth) = args.max_depth {
+		settings.max_depth = max_depth;
+	}
+	if let Some(seed) = args.seed {
+		settings.seed = Some(seed);
 	}
+	if let Some(threads) = args.threads {
+		settings.thread_count = threads;
+	}
+}
 
-	// Hits background
-	let unit_direction = ray.direction.unit_vector();
-	let t = 0.5 * (unit_direction.y + 1.0);
-	let color_vec = {
-		let start_value = Vec3 { x: 1.0, y: 1.0, z: 1.0 };
-		let end_value = Vec3 { x: 0.5, y: 0.7, z: 1.0 };
-		start_value * (1.0 - t) + end_value * t
+/// Scene with a few spheres of different materials, rendered if no scene file is given.
+fn demo_scene() -> Scene {
+	let world = {
+		let material_ground: Arc<dyn Material> = Arc::new(Lambertian {
+			albedo: Arc::new(Rgb::new(0.5, 0.5, 0.5)),
+		});
+		let material_center: Arc<dyn Material> = Arc::new(Lambertian {
+			albedo: Arc::new(Rgb::new(0.7, 0.3, 0.3)),
+		});
+		let material_left: Arc<dyn Material> = Arc::new(Dielectric {
+			index_of_refraction: -0.4,
+		});
+		let material_right: Arc<dyn Material> = Arc::new(Metal {
+			albedo: Arc::new(Rgb::new(0.8, 0.6, 0.2)),
+			fuzziness: 0.3,
+		});
+
+		let hittables: Vec<Box<dyn Hittable>> = vec![
+			Box::new(Plane {
+				point: Vec3 { x: 0.0, y: -0.5, z: 0.0 },
+				normal: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+				material: Arc::clone(&material_ground),
+			}),
+			Box::new(Sphere {
+				center: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+				radius: 0.5,
+				material: Arc::clone(&material_center),
+			}),
+			Box::new(Sphere {
+				center: Vec3 { x: -1.0, y: 0.0, z: -1.0 },
+				radius: 0.5,
+				material: Arc::clone(&material_left),
+			}),
+			Box::new(Sphere {
+				center: Vec3 { x: -0.3, y: -0.3, z: -0.5 },
+				radius: 0.2,
+				material: Arc::clone(&material_right),
+			}),
+		];
+		Bvh::new(hittables)
 	};
-	Color::from(color_vec)
-}
 
 
-/*
-struct Scene {
-    width: u32,
-    height: u32,
-    samples_per_pixel: u32,
-    max_depth: u32,
-    aspect_ratio: f64,
-    image: Image,
-    world: Vec<Box<dyn Hittable>>,
-    camera: Camera,
+	let settings = RenderSettings {
+		width: 480,
+		height: 270,
+		samples_per_pixel: 30,
+		max_depth: 30,
+		..RenderSettings::default()
+	};
+	Scene { camera: Camera::default(), settings, world }
 }
- */
+
 
 /*trait Reflection {
 	fn reflect(&self, normal: Vec3) -> Vec3;
@@ -186,16 +303,3 @@ impl Reflection for LambertianReflection {
 		normal + Vec3::random_unit_vector()
 	}
 }*/
-
-
-/*#[derive(Parser, Debug)]
-struct Args {
-	#[clap(short, long, default_value = "720")]
-	image_width: usize,
-
-	#[clap(short, long, default_value = "10")]
-	antialiasing_samples_per_pixel: usize,
-
-	/// Szene description as JSON
-	szene: String,
-}*/
\ No newline at end of file
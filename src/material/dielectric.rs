@@ -1,4 +1,4 @@
-use crate::{Color, Ray};
+use crate::{random, Ray, Rgb};
 use crate::hittable::{Hit, IntersectionSide};
 use crate::material::Material;
 
@@ -8,8 +8,8 @@ pub struct Dielectric {
 }
 
 impl Material for Dielectric {
-	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Color)> {
-		let attenuation_color = Color { r: 255, g: 255, b: 255 };
+	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Rgb)> {
+		let attenuation_color = Rgb::WHITE;
 		let refraction_ratio = match hit.intersection_side {
 			IntersectionSide::Inside => self.index_of_refraction,
 			IntersectionSide::Outside => 1.0 / self.index_of_refraction,
@@ -22,7 +22,7 @@ impl Material for Dielectric {
 		// "real glass has reflectivity that varies with angle — look at a window at a steep angle and it becomes a
 		// mirror. [...] almost everybody uses a cheap and surprisingly accurate polynomial approximation by Christophe
 		// Schlick"
-		let reflect = Self::reflectance(cos_theta, refraction_ratio) > rand::random::<f64>();
+		let reflect = Self::reflectance(cos_theta, refraction_ratio) > random::gen_range(0.0..1.0);
 		let direction = if cannot_refract || reflect {
 			unit_direction.reflect(hit.normal)
 		} else {
@@ -32,6 +32,7 @@ impl Material for Dielectric {
 		let scattered = Ray {
 			origin: hit.point,
 			direction,
+			time: ray.time,
 		};
 		Some((scattered, attenuation_color))
 	}
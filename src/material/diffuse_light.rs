@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use crate::{Ray, Rgb};
+use crate::hittable::Hit;
+use crate::material::Material;
+use crate::texture::Texture;
+
+/// Light source that emits the same radiance in all directions and from both sides of the surface, and doesn't
+/// reflect any light.
+#[derive(Debug)]
+pub struct DiffuseLight {
+	/// Emitted radiance. Components above 1.0 make the light brighter than a white surface lit by the sky.
+	pub emit: Arc<dyn Texture>,
+}
+
+impl Default for DiffuseLight {
+	/// A light that is switched off.
+	fn default() -> Self {
+		Self { emit: Arc::new(Rgb::BLACK) }
+	}
+}
+
+impl Material for DiffuseLight {
+	fn scatter(&self, _ray: Ray, _hit: Hit) -> Option<(Ray, Rgb)> {
+		None
+	}
+
+	fn emitted(&self, hit: &Hit) -> Rgb {
+		self.emit.value(hit.uv, hit.point)
+	}
+}
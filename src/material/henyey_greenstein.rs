@@ -0,0 +1,69 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::{random, Ray, Rgb, Vec3};
+use crate::hittable::Hit;
+use crate::material::Material;
+use crate::texture::Texture;
+
+/// Phase function of a participating medium that prefers scattering light forward or backward, like clouds, fog
+/// or milk. Used with a [ConstantMedium](crate::hittable::ConstantMedium).
+#[derive(Debug)]
+pub struct HenyeyGreenstein {
+	/// Fraction of the light that is scattered instead of absorbed.
+	pub albedo: Arc<dyn Texture>,
+	/// Mean cosine of the scattering angle, between -1.0 (backward) and 1.0 (forward). 0.0 scatters isotropically.
+	pub anisotropy: f64,
+}
+
+impl Material for HenyeyGreenstein {
+	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Rgb)> {
+		let scattered = Ray {
+			origin: hit.point,
+			direction: sample(ray.direction, self.anisotropy),
+			time: ray.time,
+		};
+		Some((scattered, self.albedo.value(hit.uv, hit.point)))
+	}
+}
+
+/// Returns a random unit vector distributed by the Henyey-Greenstein phase function around the `direction` the
+/// light travels in.
+pub(crate) fn sample(direction: Vec3, anisotropy: f64) -> Vec3 {
+	let g = anisotropy;
+	let cos_theta = if g.abs() < 1e-3 {
+		random::gen_range(-1.0..1.0)
+	} else {
+		// Inverts the cumulative distribution of the cosine
+		let fraction = (1.0 - g * g) / (1.0 - g + 2.0 * g * random::gen_range(0.0..1.0));
+		((1.0 + g * g - fraction * fraction) / (2.0 * g)).clamp(-1.0, 1.0)
+	};
+	let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+	let phi = random::gen_range(0.0..2.0 * PI);
+	let forward = direction.unit_vector();
+	let (first, second) = forward.orthonormal_basis();
+	first * (sin_theta * phi.cos()) + second * (sin_theta * phi.sin()) + forward * cos_theta
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn mean_cosine() {
+		random::seed(7);
+		let direction = Vec3 { x: 1.0, y: 2.0, z: -2.0 };
+		for anisotropy in [-0.7, 0.0, 0.3, 0.9] {
+			let count = 20_000;
+			let mean_cosine = (0..count)
+				.map(|_| {
+					let sample = sample(direction, anisotropy);
+					assert!((sample.length() - 1.0).abs() < 1e-9);
+					sample.dot(direction.unit_vector())
+				})
+				.sum::<f64>() / count as f64;
+			assert!((mean_cosine - anisotropy).abs() < 0.02, "{} for anisotropy {}", mean_cosine, anisotropy);
+		}
+	}
+}
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use crate::{Ray, Rgb, Vec3};
+use crate::hittable::Hit;
+use crate::material::Material;
+use crate::texture::Texture;
+
+/// Phase function of a participating medium that scatters light uniformly in all directions, regardless of where
+/// it came from. Used with a [ConstantMedium](crate::hittable::ConstantMedium), e.g. for smoke.
+#[derive(Debug)]
+pub struct Isotropic {
+	/// Fraction of the light that is scattered instead of absorbed.
+	pub albedo: Arc<dyn Texture>,
+}
+
+impl Material for Isotropic {
+	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Rgb)> {
+		let scattered = Ray {
+			origin: hit.point,
+			direction: Vec3::random_unit_vector(),
+			time: ray.time,
+		};
+		Some((scattered, self.albedo.value(hit.uv, hit.point)))
+	}
+}
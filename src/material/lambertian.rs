@@ -1,14 +1,24 @@
-use crate::{Color, Ray, Vec3};
+use std::sync::Arc;
+
+use crate::{Ray, Rgb, Vec3};
 use crate::hittable::Hit;
 use crate::material::Material;
+use crate::texture::Texture;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Lambertian {
-	pub albedo: Color,
+	pub albedo: Arc<dyn Texture>,
+}
+
+impl Default for Lambertian {
+	/// Black, i.e. a surface absorbing all light.
+	fn default() -> Self {
+		Self { albedo: Arc::new(Rgb::BLACK) }
+	}
 }
 
 impl Material for Lambertian {
-	fn scatter(&self, _ray: Ray, hit: Hit) -> Option<(Ray, Color)> {
+	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Rgb)> {
 		// scatter always and attenuate by its reflectance?!
 		let mut scatter_direction = hit.normal + Vec3::random_unit_vector();
 
@@ -18,11 +28,12 @@ impl Material for Lambertian {
 			scatter_direction = hit.normal;
 		}
 
+		let attenuation = self.albedo.value(hit.uv, hit.point);
 		let scattered = Ray {
 			origin: hit.point,
 			direction: scatter_direction,
+			time: ray.time,
 		};
-		let attenuation = self.albedo;
 		Some((scattered, attenuation))
 	}
 }
\ No newline at end of file
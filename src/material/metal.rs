@@ -1,21 +1,25 @@
-use crate::{Color, Ray, Vec3};
+use std::sync::Arc;
+
+use crate::{Ray, Rgb, Vec3};
 use crate::hittable::Hit;
 use crate::material::Material;
+use crate::texture::Texture;
 
 #[derive(Debug)]
 pub struct Metal {
-	pub albedo: Color,
+	pub albedo: Arc<dyn Texture>,
 	pub fuzziness: f64,
 }
 
 impl Material for Metal {
-	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Color)> {
+	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Rgb)> {
 		let reflected = ray.direction.unit_vector().reflect(hit.normal);
 		let scattered = Ray {
 			origin: hit.point,
 			direction: reflected + (Vec3::random_in_unit_sphere() * self.fuzziness),
+			time: ray.time,
 		};
-		let attenuation = self.albedo;
+		let attenuation = self.albedo.value(hit.uv, hit.point);
 
 		// ?!?!
 		if scattered.direction.dot(hit.normal) > 0.0 {
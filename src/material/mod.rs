@@ -1,17 +1,31 @@
 use std::fmt::Debug;
 
 pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
+pub use henyey_greenstein::HenyeyGreenstein;
+pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
 
-use crate::{Color, Ray};
+use crate::{Ray, Rgb};
 use crate::hittable::Hit;
 
 mod lambertian;
 mod metal;
 mod dielectric;
+mod diffuse_light;
+mod isotropic;
+pub(crate) mod henyey_greenstein;
 
-pub trait Material: Debug {
+/// A material describes how light is scattered at the surface of an object.
+///
+/// Materials are shared between the render threads, so they have to be [Send] and [Sync].
+pub trait Material: Debug + Send + Sync {
 	/// Returns the attenuation (reflective radiation) and the scattered ray.
-	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Color)>;
+	fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Rgb)>;
+
+	/// Returns the radiance the surface emits at the hit point. Materials that aren't light sources emit nothing.
+	fn emitted(&self, _hit: &Hit) -> Rgb {
+		Rgb::BLACK
+	}
 }
\ No newline at end of file
@@ -0,0 +1,533 @@
+//! Loader for Wavefront OBJ models and their MTL material libraries.
+//!
+//! Faces become [TriangleMesh]es, one per group and material. Polygons with more than three vertices are split into
+//! a fan of triangles, which is correct for the convex polygons exporters write. Vertex normals and texture
+//! coordinates are kept if every vertex of a mesh has them. Statements other than vertices, faces, groups, objects
+//! and materials (e.g. lines, smoothing groups and free-form geometry) are ignored.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{Image, Rgb, TransferFunction, Vec3};
+use crate::hittable::TriangleMesh;
+use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::texture::{ImageTexture, Texture};
+
+/// Triangles of an OBJ group that share a material.
+#[derive(Debug)]
+pub struct Group {
+	/// Name given by the last `g` or `o` statement before the faces, or `default`.
+	pub name: String,
+	/// Name given by the last `usemtl` statement before the faces, if any.
+	pub material_name: Option<String>,
+	pub mesh: TriangleMesh,
+}
+
+/// Material of an MTL material library, with the defaults of the MTL format for missing properties.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MtlMaterial {
+	pub name: String,
+	/// Diffuse color (`Kd`).
+	pub diffuse: Rgb,
+	/// Specular color (`Ks`).
+	pub specular: Rgb,
+	/// Specular exponent (`Ns`) between 0 and 1000, higher values give sharper highlights.
+	pub shininess: f64,
+	/// Index of refraction (`Ni`) of transparent materials.
+	pub index_of_refraction: f64,
+	/// Opacity (`d`, or `1 - Tr`), where 1 is fully opaque.
+	pub dissolve: f64,
+	/// Emitted radiance (`Ke`).
+	pub emission: Rgb,
+	/// Texture of the diffuse color (`map_Kd`), relative to the material library.
+	pub diffuse_map: Option<PathBuf>,
+}
+
+impl MtlMaterial {
+	fn new(name: &str) -> Self {
+		Self {
+			name: name.to_string(),
+			diffuse: Rgb::new(0.8, 0.8, 0.8),
+			specular: Rgb::BLACK,
+			shininess: 0.0,
+			index_of_refraction: 1.5,
+			dissolve: 1.0,
+			emission: Rgb::BLACK,
+			diffuse_map: None,
+		}
+	}
+
+	/// Converts the material to the closest [Material] of this crate, using the loaded texture `diffuse_map` instead
+	/// of the diffuse color if given:
+	///
+	/// - Emissive materials become a [DiffuseLight].
+	/// - Transparent materials become a [Dielectric].
+	/// - Materials whose specular color is brighter than their diffuse color become a [Metal], which is the rougher
+	///   the lower the shininess is.
+	/// - All others become [Lambertian].
+	pub fn to_material(&self, diffuse_map: Option<Arc<dyn Texture>>) -> Arc<dyn Material> {
+		if self.emission.max_component() > 0.0 {
+			Arc::new(DiffuseLight { emit: Arc::new(self.emission) })
+		} else if self.dissolve < 1.0 {
+			Arc::new(Dielectric { index_of_refraction: self.index_of_refraction })
+		} else if self.specular.luminance() > self.diffuse.luminance() {
+			// Width of a Phong lobe with the given exponent, which is 1 for an exponent of 0
+			let fuzziness = (2.0 / (self.shininess.max(0.0) + 2.0)).sqrt();
+			Arc::new(Metal { albedo: Arc::new(self.specular), fuzziness })
+		} else {
+			Arc::new(Lambertian { albedo: diffuse_map.unwrap_or_else(|| Arc::new(self.diffuse)) })
+		}
+	}
+}
+
+/// Error in an OBJ file or one of its material libraries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjError {
+	/// File containing the error, if known.
+	pub file: Option<PathBuf>,
+	/// Line number starting at 1, or 0 if the error doesn't belong to a line.
+	pub line: usize,
+	pub reason: String,
+}
+
+impl ObjError {
+	fn new(line: usize, reason: impl Into<String>) -> Self {
+		Self { file: None, line, reason: reason.into() }
+	}
+}
+
+impl fmt::Display for ObjError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match (&self.file, self.line) {
+			(Some(file), 0) => write!(f, "{}: {}", file.display(), self.reason),
+			(Some(file), line) => write!(f, "{}:{}: {}", file.display(), line, self.reason),
+			(None, 0) => write!(f, "{}", self.reason),
+			(None, line) => write!(f, "{}: {}", line, self.reason),
+		}
+	}
+}
+
+impl Error for ObjError {}
+
+/// Loads the OBJ file at `path`. Material libraries are looked up relative to its directory, textures relative to
+/// their material library.
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Group>, ObjError> {
+	let path = path.as_ref();
+	let directory = path.parent().unwrap_or(Path::new(""));
+	let source = fs::read_to_string(path)
+		.map_err(|error| ObjError { file: Some(path.to_path_buf()), ..ObjError::new(0, error.to_string()) })?;
+	parse(&source, |file| fs::read(directory.join(file)))
+		.map_err(|error| {
+			let file = match &error.file {
+				Some(library) => directory.join(library),
+				None => path.to_path_buf(),
+			};
+			ObjError { file: Some(file), ..error }
+		})
+}
+
+/// Parses an OBJ model. `read_file` returns the content of the material library or texture at the given path,
+/// which is relative to the model.
+///
+/// Textures are expected to be PPM or PNG images with sRGB colors. Errors in a material library have its path as
+/// [ObjError::file], errors in the model itself have none.
+pub fn parse(source: &str, mut read_file: impl FnMut(&Path) -> io::Result<Vec<u8>>) -> Result<Vec<Group>, ObjError> {
+	let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+	let mut positions = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+	let mut builders: Vec<MeshBuilder> = Vec::new();
+	let mut group = "default".to_string();
+	let mut material_name: Option<String> = None;
+
+	for (line_index, line) in source.lines().enumerate() {
+		let line_number = line_index + 1;
+		let error = |reason: String| ObjError::new(line_number, reason);
+		let Some((keyword, arguments)) = statement(line) else { continue };
+
+		match keyword {
+			"v" => positions.push(Vec3::from(numbers::<3>(&arguments, 3).map_err(error)?)),
+			"vn" => normals.push(Vec3::from(numbers::<3>(&arguments, 3).map_err(error)?)),
+			"vt" => {
+				let [u, v] = numbers::<2>(&arguments, 1).map_err(error)?;
+				uvs.push([u, v]);
+			}
+			"f" => {
+				if arguments.len() < 3 {
+					return Err(error("faces need at least three vertices".to_string()));
+				}
+				let vertices = arguments.iter()
+					.map(|vertex| {
+						face_vertex(vertex, positions.len(), uvs.len(), normals.len())
+							.map_err(|reason| format!("invalid vertex `{}`: {}", vertex, reason))
+					})
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(error)?;
+
+				let builder = match builders.iter().position(|builder| {
+					builder.group == group && builder.material_name == material_name
+				}) {
+					Some(index) => &mut builders[index],
+					None => {
+						builders.push(MeshBuilder::new(group.clone(), material_name.clone()));
+						builders.last_mut().unwrap()
+					}
+				};
+				// Fan triangulation around the first vertex
+				for index in 1..vertices.len() - 1 {
+					let triangle = [vertices[0], vertices[index], vertices[index + 1]]
+						.map(|vertex| builder.vertex(vertex, &positions, &uvs, &normals));
+					builder.triangles.push(triangle);
+				}
+			}
+			"g" | "o" => {
+				group = if arguments.is_empty() { "default".to_string() } else { arguments.join(" ") };
+			}
+			"usemtl" => {
+				let name = arguments.join(" ");
+				if !materials.contains_key(&name) {
+					return Err(error(format!("unknown material `{}`", name)));
+				}
+				material_name = Some(name);
+			}
+			"mtllib" => {
+				for library in arguments {
+					load_library(Path::new(library), &mut read_file, &mut materials)
+						.map_err(|error| match error.file {
+							Some(_) => error,
+							// The library itself couldn't be read, which is an error of the `mtllib` statement
+							None => ObjError::new(line_number, error.reason),
+						})?;
+				}
+			}
+			_ => {}
+		}
+	}
+
+	let default_material: Arc<dyn Material> = Arc::new(Lambertian { albedo: Arc::new(MtlMaterial::new("").diffuse) });
+	builders.into_iter()
+		.map(|builder| {
+			let material = match &builder.material_name {
+				Some(name) => Arc::clone(&materials[name]),
+				None => Arc::clone(&default_material),
+			};
+			builder.build(material)
+		})
+		.collect()
+}
+
+/// Reads the material library at `path` with `read_file` and adds its materials to `materials`, loading their
+/// textures.
+///
+/// Errors in the library have it as [ObjError::file]. If the library itself can't be read, the error has no file.
+fn load_library(
+	path: &Path,
+	read_file: &mut impl FnMut(&Path) -> io::Result<Vec<u8>>,
+	materials: &mut HashMap<String, Arc<dyn Material>>,
+) -> Result<(), ObjError> {
+	let source = read_file(path)
+		.and_then(|bytes| String::from_utf8(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)))
+		.map_err(|reason| ObjError::new(0, format!("cannot read material library `{}`: {}", path.display(), reason)))?;
+	let in_library = |error: ObjError| ObjError { file: Some(path.to_path_buf()), ..error };
+
+	for material in parse_mtl(&source).map_err(in_library)? {
+		let diffuse_map: Option<Arc<dyn Texture>> = match &material.diffuse_map {
+			Some(texture) => {
+				let image = read_file(&path.parent().unwrap_or(Path::new("")).join(texture))
+					.and_then(|bytes| Image::decode(&bytes))
+					.map_err(|reason| {
+						in_library(ObjError::new(0, format!("cannot read texture `{}`: {}", texture.display(), reason)))
+					})?;
+				Some(Arc::new(ImageTexture::from_image(&image, TransferFunction::Srgb)))
+			}
+			None => None,
+		};
+		materials.insert(material.name.clone(), material.to_material(diffuse_map));
+	}
+	Ok(())
+}
+
+/// Parses an MTL material library.
+pub fn parse_mtl(source: &str) -> Result<Vec<MtlMaterial>, ObjError> {
+	let mut materials: Vec<MtlMaterial> = Vec::new();
+
+	for (line_index, line) in source.lines().enumerate() {
+		let line_number = line_index + 1;
+		let error = |reason: String| ObjError::new(line_number, reason);
+		let Some((keyword, arguments)) = statement(line) else { continue };
+
+		if keyword == "newmtl" {
+			if arguments.is_empty() {
+				return Err(error("materials need a name".to_string()));
+			}
+			materials.push(MtlMaterial::new(&arguments.join(" ")));
+			continue;
+		}
+		let Some(material) = materials.last_mut() else {
+			return Err(error(format!("`{}` before the first `newmtl`", keyword)));
+		};
+
+		let color = |arguments: &[&str]| {
+			// A single value is used for all three components
+			let [r, g, b] = match arguments.len() {
+				1 => numbers::<1>(arguments, 1).map(|[value]| [value; 3]),
+				_ => numbers::<3>(arguments, 3),
+			}?;
+			Ok::<_, String>(Rgb::new(r, g, b))
+		};
+		let number = |arguments: &[&str]| numbers::<1>(arguments, 1).map(|[value]| value);
+		match keyword {
+			"Kd" => material.diffuse = color(&arguments).map_err(error)?,
+			"Ks" => material.specular = color(&arguments).map_err(error)?,
+			"Ke" => material.emission = color(&arguments).map_err(error)?,
+			"Ns" => material.shininess = number(&arguments).map_err(error)?,
+			"Ni" => material.index_of_refraction = number(&arguments).map_err(error)?,
+			"d" => material.dissolve = number(&arguments).map_err(error)?,
+			"Tr" => material.dissolve = 1.0 - number(&arguments).map_err(error)?,
+			"map_Kd" => {
+				// Options like `-s 1 1 1` precede the file name
+				let file = arguments.last().ok_or_else(|| error("missing texture file".to_string()))?;
+				material.diffuse_map = Some(PathBuf::from(file));
+			}
+			_ => {}
+		}
+	}
+
+	Ok(materials)
+}
+
+/// Splits a line into its keyword and arguments, ignoring comments. Returns `None` for empty lines.
+fn statement(line: &str) -> Option<(&str, Vec<&str>)> {
+	let line = match line.split_once('#') {
+		Some((statement, _comment)) => statement,
+		None => line,
+	};
+	let mut words = line.split_whitespace();
+	let keyword = words.next()?;
+	Some((keyword, words.collect()))
+}
+
+/// Parses up to `N` numbers from the `arguments`, requiring at least `required` of them. Missing ones are zero,
+/// additional ones are ignored.
+fn numbers<const N: usize>(arguments: &[&str], required: usize) -> Result<[f64; N], String> {
+	if arguments.len() < required {
+		return Err(format!("expected {} numbers, found {}", required, arguments.len()));
+	}
+	let mut numbers = [0.0; N];
+	for (number, argument) in numbers.iter_mut().zip(arguments) {
+		*number = argument.parse::<f64>()
+			.ok()
+			.filter(|number| number.is_finite())
+			.ok_or_else(|| format!("`{}` is not a number", argument))?;
+	}
+	Ok(numbers)
+}
+
+/// Indices of a face vertex into the position, texture coordinate and normal lists.
+type FaceVertex = (usize, Option<usize>, Option<usize>);
+
+/// Parses a face vertex `position[/[uv][/normal]]` and resolves its one-based or negative (relative to the end)
+/// indices to zero-based ones.
+fn face_vertex(vertex: &str, position_count: usize, uv_count: usize, normal_count: usize) -> Result<FaceVertex, String> {
+	let resolve = |index: &str, count: usize| {
+		let index: i64 = index.parse().map_err(|_| format!("`{}` is not an index", index))?;
+		let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+		if index == 0 || !(0..count as i64).contains(&resolved) {
+			return Err(format!("index {} is out of range", index));
+		}
+		Ok(resolved as usize)
+	};
+	let optional = |index: Option<&str>, count: usize| match index {
+		None | Some("") => Ok(None),
+		Some(index) => resolve(index, count).map(Some),
+	};
+
+	let mut indices = vertex.split('/');
+	let position = resolve(indices.next().unwrap_or(""), position_count)?;
+	let uv = optional(indices.next(), uv_count)?;
+	let normal = optional(indices.next(), normal_count)?;
+	if indices.next().is_some() {
+		return Err("too many indices".to_string());
+	}
+	Ok((position, uv, normal))
+}
+
+/// Collects the triangles of one [Group], giving every distinct combination of position, texture coordinates and
+/// normal its own vertex.
+struct MeshBuilder {
+	group: String,
+	material_name: Option<String>,
+	vertices: HashMap<FaceVertex, usize>,
+	positions: Vec<Vec3>,
+	uvs: Vec<Option<[f64; 2]>>,
+	normals: Vec<Option<Vec3>>,
+	triangles: Vec<[usize; 3]>,
+}
+
+impl MeshBuilder {
+	fn new(group: String, material_name: Option<String>) -> Self {
+		Self {
+			group,
+			material_name,
+			vertices: HashMap::new(),
+			positions: Vec::new(),
+			uvs: Vec::new(),
+			normals: Vec::new(),
+			triangles: Vec::new(),
+		}
+	}
+
+	/// Returns the index of the mesh vertex for the face vertex, adding it if it is new.
+	fn vertex(&mut self, vertex: FaceVertex, positions: &[Vec3], uvs: &[[f64; 2]], normals: &[Vec3]) -> usize {
+		*self.vertices.entry(vertex).or_insert_with(|| {
+			let (position, uv, normal) = vertex;
+			self.positions.push(positions[position]);
+			self.uvs.push(uv.map(|uv| uvs[uv]));
+			self.normals.push(normal.map(|normal| normals[normal]));
+			self.positions.len() - 1
+		})
+	}
+
+	fn build(self, material: Arc<dyn Material>) -> Result<Group, ObjError> {
+		// Meshes have either normals and texture coordinates for every vertex or none at all
+		let normals = self.normals.into_iter().collect::<Option<Vec<_>>>().unwrap_or_default();
+		let uvs = self.uvs.into_iter().collect::<Option<Vec<_>>>().unwrap_or_default();
+		let mesh = TriangleMesh::new(self.positions, normals, uvs, self.triangles, material)
+			.map_err(|error| ObjError::new(0, error.to_string()))?;
+		Ok(Group { name: self.group, material_name: self.material_name, mesh })
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::hittable::Hittable;
+	use crate::Ray;
+
+	use super::*;
+
+	fn no_files(path: &Path) -> io::Result<Vec<u8>> {
+		Err(io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display())))
+	}
+
+	#[test]
+	fn quad() {
+		let obj = "
+			# Unit square in the xy plane
+			v 0 0 0
+			v 1 0 0
+			v 1 1 0
+			v 0 1 0
+			vt 0 0
+			vt 1 0
+			vt 1 1
+			vt 0 1
+			vn 0 0 1
+			f 1/1/1 2/2/1 3/3/1 -1/-1/-1
+		";
+		let groups = parse(obj, no_files).unwrap();
+		assert_eq!(groups.len(), 1);
+		let mesh = &groups[0].mesh;
+		assert_eq!(groups[0].name, "default");
+		assert_eq!(mesh.triangles(), &[[0, 1, 2], [0, 2, 3]]);
+		assert_eq!(mesh.uvs()[2], [1.0, 1.0]);
+		assert_eq!(mesh.normals().len(), 4);
+
+		let ray = Ray {
+			origin: Vec3 { x: 0.25, y: 0.75, z: 1.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		assert_eq!(mesh.hits(ray, 0.0, f64::INFINITY).map(|hit| hit.t), Some(1.0));
+	}
+
+	#[test]
+	fn groups_and_materials() {
+		let mtl = "
+			newmtl red
+			Kd 0.8 0.1 0.1
+			map_Kd -s 2 2 1 textures/red.ppm
+			newmtl mirror
+			Kd 0 0 0
+			Ks 0.9
+			Ns 1000
+			newmtl lamp
+			Ke 4 4 4
+			newmtl glass
+			Ni 1.33
+			d 0.1
+		";
+		let obj = "
+			mtllib materials/scene.mtl
+			v 0 0 0
+			v 1 0 0
+			v 0 1 0
+			g first
+			usemtl red
+			f 1 2 3
+			usemtl mirror
+			f 1 2 3
+			g second
+			usemtl red
+			f 1 2 3
+			f 3 2 1
+		";
+		let groups = parse(obj, |path| match path.to_str().unwrap() {
+			"materials/scene.mtl" => Ok(mtl.as_bytes().to_vec()),
+			"materials/textures/red.ppm" => Ok(b"P3 1 1 255 255 0 0".to_vec()),
+			_ => no_files(path),
+		}).unwrap();
+		let names: Vec<_> = groups.iter()
+			.map(|group| (group.name.as_str(), group.material_name.as_deref().unwrap()))
+			.collect();
+		assert_eq!(names, [("first", "red"), ("first", "mirror"), ("second", "red")]);
+		assert_eq!(groups[2].mesh.triangles().len(), 2);
+
+		let hit = groups[0].mesh.hits(Ray {
+			origin: Vec3 { x: 0.1, y: 0.1, z: 1.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		}, 0.0, f64::INFINITY).unwrap();
+		assert!(format!("{:?}", hit.material).contains("ImageTexture"));
+
+		let materials = parse_mtl(mtl).unwrap();
+		assert_eq!(materials[1].specular, Rgb::new(0.9, 0.9, 0.9));
+		assert_eq!(materials[0].diffuse_map, Some(PathBuf::from("textures/red.ppm")));
+		let kinds: Vec<_> = materials.iter()
+			.map(|material| format!("{:?}", material.to_material(None)).split_whitespace().next().unwrap().to_string())
+			.collect();
+		assert_eq!(kinds, ["Lambertian", "Metal", "DiffuseLight", "Dielectric"]);
+	}
+
+	#[test]
+	fn errors() {
+		let error = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\n\nf 1 2 4", no_files).unwrap_err();
+		assert_eq!(error.to_string(), "5: invalid vertex `4`: index 4 is out of range");
+
+		let error = parse("v 0 zero 0", no_files).unwrap_err();
+		assert_eq!(error, ObjError::new(1, "`zero` is not a number"));
+
+		let error = parse("usemtl missing", no_files).unwrap_err();
+		assert_eq!(error.reason, "unknown material `missing`");
+
+		let error = parse("mtllib a.mtl", |_| Ok(b"newmtl a\nKd 1 1".to_vec())).unwrap_err();
+		assert_eq!(error.to_string(), "a.mtl:2: expected 3 numbers, found 2");
+
+		let error = parse("\nmtllib a.mtl", no_files).unwrap_err();
+		assert_eq!(error.to_string(), "2: cannot read material library `a.mtl`: a.mtl not found");
+
+		let error = parse("mtllib a.mtl", |path| match path.to_str().unwrap() {
+			"a.mtl" => Ok(b"newmtl a\nmap_Kd a.png".to_vec()),
+			_ => no_files(path),
+		}).unwrap_err();
+		assert_eq!(error.to_string(), "a.mtl: cannot read texture `a.png`: a.png not found");
+
+		let error = load("does/not/exist.obj").unwrap_err();
+		assert!(error.to_string().starts_with("does/not/exist.obj: "));
+	}
+}
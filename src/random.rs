@@ -0,0 +1,40 @@
+//! Random number generation for sampling.
+//!
+//! Every thread owns its own generator. The renderer reseeds it before each pixel, so that the samples taken for a
+//! pixel don't depend on which thread renders it or in which order the pixels are rendered.
+
+use std::cell::RefCell;
+use std::ops::Range;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::SmallRng;
+
+thread_local! {
+	static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_entropy());
+}
+
+/// Reseeds the generator of the current thread, making all following random values deterministic.
+pub fn seed(seed: u64) {
+	RNG.with(|rng| *rng.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
+/// Returns a random value in the `range`.
+pub fn gen_range(range: Range<f64>) -> f64 {
+	RNG.with(|rng| rng.borrow_mut().gen_range(range))
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn seeded_is_deterministic() {
+		seed(42);
+		let first: Vec<f64> = (0..10).map(|_| gen_range(0.0..1.0)).collect();
+		seed(42);
+		let second: Vec<f64> = (0..10).map(|_| gen_range(0.0..1.0)).collect();
+		assert_eq!(first, second);
+		assert!(first.iter().all(|value| (0.0..1.0).contains(value)));
+	}
+}
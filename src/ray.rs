@@ -9,6 +9,9 @@ pub struct Ray {
 	pub origin: Vec3,
 	/// Direction.
 	pub direction: Vec3,
+	/// Point in time at which the ray travels, between 0.0 and 1.0 over the duration of a frame. Moving objects are
+	/// intersected at their position at this time, which gives motion blur.
+	pub time: f64,
 }
 
 impl Ray {
@@ -20,7 +23,7 @@ impl Ray {
 
 impl fmt::Display for Ray {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "Ray({:?} -> {:?})", self.origin, self.direction)
+		write!(f, "Ray({:?} -> {:?} at {})", self.origin, self.direction, self.time)
 	}
 }
 
@@ -33,6 +36,7 @@ mod tests {
 		let ray = Ray {
 			origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
 			direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+			time: 0.0,
 		};
 		assert_eq!(ray.at(0.0), Vec3 { x: 0.0, y: 0.0, z: 0.0 });
 		assert_eq!(ray.at(1.0), Vec3 { x: 1.0, y: 0.0, z: 0.0 });
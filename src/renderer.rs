@@ -0,0 +1,482 @@
+//! Renders a [Framebuffer] of a world of [Hittable]s as seen through a [Camera].
+
+use std::ops;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{Camera, Framebuffer, random, Ray, Rgb, Vec3};
+use crate::hittable::Hittable;
+use crate::material::henyey_greenstein;
+
+/// Color of rays that don't hit anything in the world.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Background {
+	/// The same color in every direction.
+	Solid(Rgb),
+	/// Vertical gradient from `bottom` (rays pointing straight down) to `top` (rays pointing straight up).
+	Gradient { bottom: Rgb, top: Rgb },
+}
+
+impl Background {
+	/// Returns the background color in the `direction`.
+	pub fn color(&self, direction: Vec3) -> Rgb {
+		match *self {
+			Background::Solid(color) => color,
+			Background::Gradient { bottom, top } => {
+				let t = 0.5 * (direction.unit_vector().y + 1.0);
+				bottom * (1.0 - t) + top * t
+			}
+		}
+	}
+}
+
+impl Default for Background {
+	/// White to light blue sky.
+	fn default() -> Self {
+		Background::Gradient {
+			bottom: Rgb::WHITE,
+			top: Rgb::new(0.5, 0.7, 1.0),
+		}
+	}
+}
+
+/// Homogeneous participating medium filling the space between the objects, e.g. haze or mist.
+///
+/// Rays that don't hit anything leave the fog and reach the [Background], as if the fog were a layer around the
+/// scene. Objects fade into the fog with their distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fog {
+	/// Probability per unit length of a ray being scattered.
+	pub density: f64,
+	/// Fraction of the light that is scattered instead of absorbed.
+	pub albedo: Rgb,
+	/// Mean cosine of the scattering angle of the Henyey-Greenstein phase function, between -1.0 (backward) and
+	/// 1.0 (forward). 0.0 scatters isotropically.
+	pub anisotropy: f64,
+}
+
+impl Fog {
+	/// Returns a random distance at which a ray is scattered by the fog, in units of the direction of the `ray`.
+	fn scatter_t(&self, ray: Ray) -> f64 {
+		-(1.0 - random::gen_range(0.0..1.0)).ln() / (self.density * ray.direction.length())
+	}
+}
+
+/// Settings for rendering an image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderSettings {
+	/// Width of the image in pixels.
+	pub width: usize,
+	/// Height of the image in pixels.
+	pub height: usize,
+	/// Number of rays per pixel, whose colors are averaged for antialiasing.
+	pub samples_per_pixel: usize,
+	/// Maximum number of times a ray is scattered before it is considered absorbed.
+	pub max_depth: usize,
+	/// Number of times a ray is scattered before Russian roulette may terminate it, with a probability that rises as
+	/// less light is carried along the path. The surviving paths are weighted up to keep the image unbiased. With
+	/// `None`, every path is traced until it is absorbed or reaches `max_depth`.
+	pub russian_roulette_depth: Option<usize>,
+	/// Color of rays that don't hit anything.
+	pub background: Background,
+	/// Fog between the objects, or `None` for clear air.
+	pub fog: Option<Fog>,
+	/// Number of threads rendering rows in parallel.
+	pub thread_count: usize,
+	/// Seed for the random samples. The image is the same for the same seed, regardless of the number of threads.
+	/// A random seed is chosen if this is `None`.
+	pub seed: Option<u64>,
+}
+
+impl Default for RenderSettings {
+	fn default() -> Self {
+		Self {
+			width: 480,
+			height: 270,
+			samples_per_pixel: 30,
+			max_depth: 30,
+			russian_roulette_depth: Some(3),
+			background: Background::default(),
+			fog: None,
+			thread_count: std::thread::available_parallelism().map_or(1, |count| count.get()),
+			seed: None,
+		}
+	}
+}
+
+/// Counts of how the paths traced from the camera ended, for tuning the [RenderSettings].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStatistics {
+	/// Number of paths traced, one per sample.
+	pub paths: u64,
+	/// Number of rays traced along all paths, i.e. one more than the number of times they were scattered.
+	pub rays: u64,
+	/// Paths that left the world and reached the background.
+	pub escaped: u64,
+	/// Paths absorbed by a material, e.g. a light source.
+	pub absorbed: u64,
+	/// Paths terminated by Russian roulette.
+	pub terminated: u64,
+	/// Paths cut off at the maximum depth, losing the light they would have gathered further on.
+	pub max_depth_reached: u64,
+}
+
+impl RenderStatistics {
+	/// Returns the average number of rays per path.
+	pub fn average_path_length(&self) -> f64 {
+		if self.paths == 0 { 0.0 } else { self.rays as f64 / self.paths as f64 }
+	}
+}
+
+impl ops::AddAssign for RenderStatistics {
+	fn add_assign(&mut self, other: Self) {
+		self.paths += other.paths;
+		self.rays += other.rays;
+		self.escaped += other.escaped;
+		self.absorbed += other.absorbed;
+		self.terminated += other.terminated;
+		self.max_depth_reached += other.max_depth_reached;
+	}
+}
+
+/// Renders images according to its [RenderSettings].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Renderer {
+	pub settings: RenderSettings,
+}
+
+impl Renderer {
+	pub fn new(settings: RenderSettings) -> Self {
+		Self { settings }
+	}
+
+	/// Renders the `world` as seen through the `camera`. The resulting linear radiance can be converted to an
+	/// [Image](crate::Image) with [Framebuffer::to_image].
+	pub fn render(&self, camera: &Camera, world: &dyn Hittable) -> Framebuffer {
+		self.render_with_progress(camera, world, |_| {})
+	}
+
+	/// Renders the `world` as seen through the `camera`. `progress` is called with the number of rendered rows
+	/// every time a row is finished, possibly from multiple threads at the same time.
+	pub fn render_with_progress(
+		&self,
+		camera: &Camera,
+		world: &dyn Hittable,
+		progress: impl Fn(usize) + Sync,
+	) -> Framebuffer {
+		self.render_with_statistics(camera, world, progress).0
+	}
+
+	/// Renders the `world` like [Renderer::render_with_progress], and also returns [RenderStatistics] about the
+	/// traced paths.
+	pub fn render_with_statistics(
+		&self,
+		camera: &Camera,
+		world: &dyn Hittable,
+		progress: impl Fn(usize) + Sync,
+	) -> (Framebuffer, RenderStatistics) {
+		let settings = &self.settings;
+		let seed = settings.seed.unwrap_or_else(rand::random);
+		let mut framebuffer = Framebuffer::new(settings.width, settings.height);
+
+		// The threads take the next unrendered row until all rows are rendered
+		let rows = Mutex::new(framebuffer.rows.iter_mut().enumerate());
+		let rendered_row_count = AtomicUsize::new(0);
+		let mut statistics = RenderStatistics::default();
+		std::thread::scope(|scope| {
+			let threads: Vec<_> = (0..settings.thread_count.max(1))
+				.map(|_| scope.spawn(|| {
+					// Each thread counts its own paths, which are summed up at the end
+					let mut statistics = RenderStatistics::default();
+					while let Some((y, row)) = rows.lock().unwrap().next() {
+						for (x, pixel) in row.iter_mut().enumerate() {
+							random::seed(pixel_seed(seed, x, y, settings.width));
+							*pixel = self.render_pixel(camera, world, x, y, &mut statistics);
+						}
+						progress(rendered_row_count.fetch_add(1, Ordering::Relaxed) + 1);
+					}
+					statistics
+				}))
+				.collect();
+			for thread in threads {
+				statistics += thread.join().unwrap();
+			}
+		});
+
+		(framebuffer, statistics)
+	}
+
+	/// Renders the pixel at `x`, `y` by averaging the colors of multiple rays through it (antialiasing).
+	fn render_pixel(
+		&self,
+		camera: &Camera,
+		world: &dyn Hittable,
+		x: usize,
+		y: usize,
+		statistics: &mut RenderStatistics,
+	) -> Rgb {
+		let settings = &self.settings;
+		let mut radiance = Rgb::BLACK;
+		for _sample in 0..settings.samples_per_pixel {
+			// Offset vectors from the lower upper left corner into the pixel of the viewport
+			let horizontal_scalar = random_scalar_sample(x, settings.width);
+			let vertical_scalar = random_scalar_sample(y, settings.height);
+			let ray = camera.ray(horizontal_scalar, vertical_scalar);
+			radiance += self.trace_path(ray, world, settings.max_depth, statistics);
+		}
+		radiance / settings.samples_per_pixel as f64
+	}
+
+	/// Calculates the color for the `ray` by tracing it through the `world`.
+	/// The `depth` parameter limits the number of times the ray is scattered.
+	pub fn ray_color(&self, ray: Ray, world: &dyn Hittable, depth: usize) -> Rgb {
+		self.trace_path(ray, world, depth, &mut RenderStatistics::default())
+	}
+
+	/// Traces the path of light backwards from the `ray` through the `world`, scattering at most `max_depth - 1`
+	/// times, and counts how it ends in the `statistics`.
+	///
+	/// Instead of recursing for each bounce, the light that reaches the camera is summed up along the path, each
+	/// contribution weighted by the throughput, i.e. the product of the attenuations so far.
+	fn trace_path(
+		&self,
+		mut ray: Ray,
+		world: &dyn Hittable,
+		max_depth: usize,
+		statistics: &mut RenderStatistics,
+	) -> Rgb {
+		let settings = &self.settings;
+		statistics.paths += 1;
+		let mut radiance = Rgb::BLACK;
+		let mut throughput = Rgb::WHITE;
+		for depth in 0..max_depth {
+			statistics.rays += 1;
+			let hit = world.hits(ray, /*against shadow acne*/0.0001, f64::INFINITY);
+
+			// Scattered by the fog before reaching the object?
+			let fog_scattering = match (&settings.fog, &hit) {
+				(Some(fog), Some(hit)) => Some((fog, fog.scatter_t(ray))).filter(|(_, t)| *t < hit.t),
+				_ => None,
+			};
+			if let Some((fog, t)) = fog_scattering {
+				throughput = throughput * fog.albedo;
+				ray = Ray {
+					origin: ray.at(t),
+					direction: henyey_greenstein::sample(ray.direction, fog.anisotropy),
+					time: ray.time,
+				};
+			} else if let Some(hit) = hit {
+				let material = hit.material.clone();
+				radiance += throughput * material.emitted(&hit);
+				let Some((scattered, attenuation)) = material.scatter(ray, hit) else {
+					statistics.absorbed += 1;
+					return radiance;
+				};
+				throughput = throughput * attenuation;
+				ray = scattered;
+			} else {
+				statistics.escaped += 1;
+				return radiance + throughput * settings.background.color(ray.direction);
+			}
+
+			// Russian roulette: Continue the path with the probability of its throughput, and make up for the
+			// terminated paths by carrying more light along the continued ones
+			if settings.russian_roulette_depth.is_some_and(|roulette_depth| depth + 1 >= roulette_depth) {
+				let probability = throughput.max_component().min(1.0);
+				if random::gen_range(0.0..1.0) >= probability {
+					statistics.terminated += 1;
+					return radiance;
+				}
+				throughput = throughput / probability;
+			}
+		}
+
+		// Exceeded the ray bounce limit; no more light is gathered.
+		statistics.max_depth_reached += 1;
+		radiance
+	}
+}
+
+
+/// Derives the seed of the random generator for the pixel at `x`, `y` from the `seed` of the whole image.
+fn pixel_seed(seed: u64, x: usize, y: usize, width: usize) -> u64 {
+	// Spread the pixel index over all bits with the golden ratio, so that neighbouring pixels get unrelated seeds
+	seed ^ ((y * width + x) as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+/// Random anti-aliasing sample
+fn random_scalar_sample(iteration: usize, dimension: usize) -> f64 {
+	assert!(iteration < dimension);
+	// It's important that the random_scalar_offset goes with the iteration, not the output pixel f64, since
+	// this is not scaled by the viewport width or height.
+	let random_scalar_offset = random::gen_range(0.0..1.0);
+	// Dividing by the dimension (instead of dimension - 1) keeps the scalar in 0.0..1.0 for small images, too
+	(iteration as f64 + random_scalar_offset) / dimension as f64
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use crate::hittable::{Hit, Sphere};
+	use crate::material::{DiffuseLight, Lambertian, Material};
+
+	use super::*;
+
+	fn world() -> Vec<Box<dyn Hittable>> {
+		vec![
+			Box::new(Sphere {
+				center: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+				radius: 0.5,
+				material: Arc::new(Lambertian { albedo: Arc::new(Rgb::new(0.8, 0.4, 0.2)) }),
+			}),
+		]
+	}
+
+	fn settings() -> RenderSettings {
+		RenderSettings {
+			width: 16,
+			height: 9,
+			samples_per_pixel: 4,
+			max_depth: 5,
+			seed: Some(42),
+			..RenderSettings::default()
+		}
+	}
+
+	#[test]
+	fn dimensions() {
+		let framebuffer = Renderer::new(settings()).render(&Camera::default(), &world().as_slice());
+		assert_eq!(framebuffer.width(), 16);
+		assert_eq!(framebuffer.height(), 9);
+	}
+
+	#[test]
+	fn solid_background() {
+		let color = Rgb::new(4.0, 0.5, 0.0);
+		let renderer = Renderer::new(RenderSettings { background: Background::Solid(color), ..settings() });
+		let empty_world: &[Box<dyn Hittable>] = &[];
+		let framebuffer = renderer.render(&Camera::default(), &empty_world);
+		// Radiance above 1.0 is preserved
+		assert!(framebuffer.pixels().all(|pixel| *pixel == color));
+	}
+
+	#[test]
+	fn emission() {
+		let emit = Rgb::new(4.0, 2.0, 1.0);
+		let light: Vec<Box<dyn Hittable>> = vec![
+			Box::new(Sphere {
+				center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+				radius: 100.0,
+				material: Arc::new(DiffuseLight { emit: Arc::new(emit) }),
+			}),
+		];
+		// The camera is inside the light, which is visible in every direction
+		let framebuffer = Renderer::new(RenderSettings { background: Background::Solid(Rgb::BLACK), ..settings() })
+			.render(&Camera::default(), &light.as_slice());
+		assert!(framebuffer.pixels().all(|pixel| *pixel == emit));
+	}
+
+	#[test]
+	fn fog() {
+		// A white wall behind dense, black fog is hidden, while the background stays clear
+		let wall: Vec<Box<dyn Hittable>> = vec![
+			Box::new(Sphere {
+				center: Vec3 { x: 0.0, y: 0.0, z: -101.0 },
+				radius: 100.0,
+				material: Arc::new(DiffuseLight { emit: Arc::new(Rgb::WHITE) }),
+			}),
+		];
+		let background = Rgb::new(0.2, 0.4, 0.6);
+		let fog = Fog { density: 100.0, albedo: Rgb::BLACK, anisotropy: 0.0 };
+		let renderer = Renderer::new(RenderSettings {
+			background: Background::Solid(background),
+			fog: Some(fog),
+			..settings()
+		});
+		let ray = |direction| Ray { origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 }, direction, time: 0.0 };
+		let towards_wall = renderer.ray_color(ray(Vec3 { x: 0.0, y: 0.0, z: -1.0 }), &wall.as_slice(), 5);
+		assert_eq!(towards_wall, Rgb::BLACK);
+		let away = renderer.ray_color(ray(Vec3 { x: 0.0, y: 0.0, z: 1.0 }), &wall.as_slice(), 5);
+		assert_eq!(away, background);
+
+		// Without absorption, thin fog only redirects light between the equally bright wall and background
+		let fog = Fog { density: 0.5, albedo: Rgb::WHITE, anisotropy: 0.5 };
+		let renderer = Renderer::new(RenderSettings {
+			background: Background::Solid(Rgb::WHITE),
+			fog: Some(fog),
+			..settings()
+		});
+		let color = renderer.ray_color(ray(Vec3 { x: 0.0, y: 0.0, z: -1.0 }), &wall.as_slice(), 50);
+		assert_eq!(color, Rgb::WHITE);
+	}
+
+	/// Material that emits light and reflects half of the incoming light diffusely.
+	#[derive(Debug)]
+	struct Glowing;
+
+	impl Material for Glowing {
+		fn scatter(&self, ray: Ray, hit: Hit) -> Option<(Ray, Rgb)> {
+			let direction = hit.normal + Vec3::random_unit_vector();
+			let scattered = Ray { origin: hit.point, direction, time: ray.time };
+			Some((scattered, Rgb::new(0.5, 0.5, 0.5)))
+		}
+
+		fn emitted(&self, _hit: &Hit) -> Rgb {
+			Rgb::WHITE
+		}
+	}
+
+	#[test]
+	fn russian_roulette() {
+		// Inside a glowing sphere, every bounce adds half as much light as the one before, 2.0 in total
+		let world: Vec<Box<dyn Hittable>> = vec![
+			Box::new(Sphere { center: Vec3 { x: 0.0, y: 0.0, z: 0.0 }, radius: 1.0, material: Arc::new(Glowing) }),
+		];
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let renderer = Renderer::new(RenderSettings { russian_roulette_depth: None, ..settings() });
+		let mut statistics = RenderStatistics::default();
+		let radiance = renderer.trace_path(ray, &world.as_slice(), 60, &mut statistics);
+		assert!((radiance.r - 2.0).abs() < 1e-12);
+		assert_eq!((statistics.rays, statistics.max_depth_reached), (60, 1));
+
+		// Russian roulette terminates the paths much earlier, but they still carry as much light on average
+		random::seed(1);
+		let renderer = Renderer::new(RenderSettings { russian_roulette_depth: Some(1), ..settings() });
+		let mut statistics = RenderStatistics::default();
+		let count = 20_000;
+		let radiance = (0..count)
+			.map(|_| renderer.trace_path(ray, &world.as_slice(), 60, &mut statistics).r)
+			.sum::<f64>() / count as f64;
+		assert!((radiance - 2.0).abs() < 0.05, "{}", radiance);
+		assert_eq!(statistics.paths, count);
+		assert!(statistics.terminated > count - 10);
+		assert!(statistics.average_path_length() < 3.0);
+	}
+
+	#[test]
+	fn statistics() {
+		let world = world();
+		let (_, statistics) = Renderer::new(settings())
+			.render_with_statistics(&Camera::default(), &world.as_slice(), |_| {});
+		assert_eq!(statistics.paths, 16 * 9 * 4);
+		assert!(statistics.rays > statistics.paths);
+		let ends = statistics.escaped + statistics.absorbed + statistics.terminated + statistics.max_depth_reached;
+		assert_eq!(ends, statistics.paths);
+	}
+
+	#[test]
+	fn same_image_regardless_of_thread_count() {
+		let world = world();
+		let single = Renderer::new(RenderSettings { thread_count: 1, ..settings() })
+			.render(&Camera::default(), &world.as_slice());
+		let multi = Renderer::new(RenderSettings { thread_count: 4, ..settings() })
+			.render(&Camera::default(), &world.as_slice());
+		assert_eq!(single, multi);
+	}
+}
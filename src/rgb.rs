@@ -0,0 +1,186 @@
+use std::fmt;
+use std::ops;
+
+use crate::{Color, Vec3};
+
+/// Linear color or radiance with red, green and blue components.
+///
+/// Unlike [Color], the components are not limited to 0.0..=1.0, so an [Rgb] can describe bright light sources and
+/// unclamped rendering results. It is converted to a [Color] for display with a [TransferFunction].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rgb {
+	pub r: f64,
+	pub g: f64,
+	pub b: f64,
+}
+
+impl Rgb {
+	pub const BLACK: Rgb = Rgb { r: 0.0, g: 0.0, b: 0.0 };
+	pub const WHITE: Rgb = Rgb { r: 1.0, g: 1.0, b: 1.0 };
+
+	pub const fn new(r: f64, g: f64, b: f64) -> Self {
+		Self { r, g, b }
+	}
+
+	/// Returns the largest of the three components.
+	pub fn max_component(&self) -> f64 {
+		self.r.max(self.g).max(self.b)
+	}
+
+	/// Relative luminance (brightness perceived by the human eye) according to ITU-R BT.709.
+	pub fn luminance(&self) -> f64 {
+		0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+	}
+
+	/// Converts the linear color to an 8-bit [Color], encoding each component with the `transfer` function and
+	/// clamping it to 0.0..=1.0.
+	pub fn to_color(&self, transfer: TransferFunction) -> Color {
+		let quantize = |component: f64| (transfer.encode(component).clamp(0.0, 1.0) * u8::MAX as f64).round() as u8;
+		Color { r: quantize(self.r), g: quantize(self.g), b: quantize(self.b) }
+	}
+}
+
+/// Interprets the 8-bit components of the [Color] as linear values in 0.0..=1.0, i.e. without decoding any transfer
+/// function.
+impl From<Color> for Rgb {
+	fn from(color: Color) -> Self {
+		Rgb {
+			r: color.r as f64 / u8::MAX as f64,
+			g: color.g as f64 / u8::MAX as f64,
+			b: color.b as f64 / u8::MAX as f64,
+		}
+	}
+}
+
+impl From<Vec3> for Rgb {
+	fn from(vec: Vec3) -> Self {
+		Rgb { r: vec.x, g: vec.y, b: vec.z }
+	}
+}
+
+impl ops::Add for Rgb {
+	type Output = Rgb;
+
+	fn add(self, other: Rgb) -> Rgb {
+		Rgb { r: self.r + other.r, g: self.g + other.g, b: self.b + other.b }
+	}
+}
+
+impl ops::AddAssign for Rgb {
+	fn add_assign(&mut self, other: Rgb) {
+		*self = *self + other;
+	}
+}
+
+/// Component-wise product, e.g. to attenuate light by the color of a surface.
+impl ops::Mul for Rgb {
+	type Output = Rgb;
+
+	fn mul(self, other: Rgb) -> Rgb {
+		Rgb { r: self.r * other.r, g: self.g * other.g, b: self.b * other.b }
+	}
+}
+
+impl ops::Mul<f64> for Rgb {
+	type Output = Rgb;
+
+	fn mul(self, factor: f64) -> Rgb {
+		Rgb { r: self.r * factor, g: self.g * factor, b: self.b * factor }
+	}
+}
+
+impl ops::Div<f64> for Rgb {
+	type Output = Rgb;
+
+	fn div(self, divisor: f64) -> Rgb {
+		Rgb { r: self.r / divisor, g: self.g / divisor, b: self.b / divisor }
+	}
+}
+
+impl fmt::Display for Rgb {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "rgb({} {} {})", self.r, self.g, self.b)
+	}
+}
+
+/// Function mapping linear values to the non-linear values stored in 8-bit images, which spend more of their
+/// precision on dark tones, where the human eye is more sensitive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferFunction {
+	/// Stores the linear values unchanged.
+	Linear,
+	/// Raises the values to the power of `1 / gamma`.
+	Gamma(f64),
+	/// Piecewise curve of the sRGB standard, which is what most displays and image viewers expect.
+	Srgb,
+}
+
+impl TransferFunction {
+	/// Converts a linear value to its encoded representation.
+	pub fn encode(&self, linear: f64) -> f64 {
+		match *self {
+			TransferFunction::Linear => linear,
+			TransferFunction::Gamma(gamma) => linear.max(0.0).powf(1.0 / gamma),
+			TransferFunction::Srgb => {
+				if linear <= 0.0031308 {
+					12.92 * linear
+				} else {
+					1.055 * linear.powf(1.0 / 2.4) - 0.055
+				}
+			}
+		}
+	}
+
+	/// Converts an encoded value back to its linear representation. Inverse of [TransferFunction::encode].
+	pub fn decode(&self, encoded: f64) -> f64 {
+		match *self {
+			TransferFunction::Linear => encoded,
+			TransferFunction::Gamma(gamma) => encoded.max(0.0).powf(gamma),
+			TransferFunction::Srgb => {
+				if encoded <= 0.04045 {
+					encoded / 12.92
+				} else {
+					((encoded + 0.055) / 1.055).powf(2.4)
+				}
+			}
+		}
+	}
+}
+
+impl Default for TransferFunction {
+	/// *Gamma 2*, which is cheap to compute and close to sRGB.
+	fn default() -> Self {
+		TransferFunction::Gamma(2.0)
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn to_color_clamps() {
+		let rgb = Rgb::new(2.0, 0.5, -1.0);
+		assert_eq!(rgb.to_color(TransferFunction::Linear), Color { r: 255, g: 128, b: 0 });
+		assert_eq!(rgb.to_color(TransferFunction::Gamma(2.0)), Color { r: 255, g: 180, b: 0 });
+	}
+
+	#[test]
+	fn srgb_round_trip() {
+		for value in [0.0, 0.001, 0.04, 0.2, 0.5, 1.0] {
+			let round_trip = TransferFunction::Srgb.decode(TransferFunction::Srgb.encode(value));
+			assert!((round_trip - value).abs() < 1e-12, "{} became {}", value, round_trip);
+		}
+		assert_eq!(Rgb::new(0.5, 0.5, 0.5).to_color(TransferFunction::Srgb), Color { r: 188, g: 188, b: 188 });
+	}
+
+	#[test]
+	fn arithmetic() {
+		let rgb = Rgb::new(1.0, 2.0, 4.0);
+		assert_eq!(rgb * Rgb::new(0.5, 0.5, 0.25), Rgb::new(0.5, 1.0, 1.0));
+		assert_eq!(rgb + rgb, rgb * 2.0);
+		assert_eq!(rgb / 2.0, Rgb::new(0.5, 1.0, 2.0));
+		assert_eq!(rgb.max_component(), 4.0);
+	}
+}
@@ -0,0 +1,1422 @@
+//! Scene description files, which describe the camera, render settings, materials and objects of a scene as JSON.
+//!
+//! ```json
+//! {
+//!   "camera": { "look_from": [-2.0, 2.0, 1.0], "look_at": [0.0, 0.0, -1.0], "vertical_fov": 90.0 },
+//!   "render": { "width": 480, "height": 270, "samples_per_pixel": 30, "max_depth": 30 },
+//!   "materials": {
+//!     "ground": { "lambertian": { "albedo": [0.5, 0.5, 0.5] } }
+//!   },
+//!   "objects": [
+//!     { "plane": { "point": [0.0, -0.5, 0.0], "normal": [0.0, 1.0, 0.0], "material": "ground" } }
+//!   ]
+//! }
+//! ```
+//!
+//! All fields except `objects` are optional and default to the values of [Camera::default] and
+//! [RenderSettings::default]. Materials, objects and backgrounds are objects with their kind as the only key, and so
+//! is the camera's `projection` unless it is just a name like `"fisheye"`.
+//! Materials are referenced by their name in `materials`. Wherever a material takes a color, it also takes a texture
+//! like `{ "checker": { "even": [1.0, 1.0, 1.0], "odd": [0.0, 0.0, 0.0], "size": 0.5 } }`. Files like the Wavefront
+//! OBJ models of `model` objects are looked up relative to the scene file.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+
+use crate::{
+	obj, Background, Camera, CameraError, Fog, Image, Projection, RenderSettings, Rgb, Transform, TransferFunction,
+	Vec3,
+};
+use crate::hittable::{
+	sdf, Aabb, Bvh, Capsule, Cone, ConstantMedium, Csg, Cuboid, Cylinder, Disk, Hittable, Instance, Moving, Operation,
+	Plane, Quad, Sdf, Sphere, Torus, Triangle, TriangleMesh,
+};
+use crate::hittable::sdf::DistanceFunction;
+use crate::material::{Dielectric, DiffuseLight, HenyeyGreenstein, Isotropic, Lambertian, Material, Metal};
+use crate::texture::{Checker, Filter, ImageTexture, NoisePattern, NoiseTexture, Perlin, Texture, UvChecker, Wrap};
+
+/// Everything needed to render an image: The world, the camera looking at it and the render settings.
+#[derive(Debug)]
+pub struct Scene {
+	pub camera: Camera,
+	pub settings: RenderSettings,
+	pub world: Bvh,
+}
+
+impl Scene {
+	/// Loads the scene description file at `path`.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+		let path = path.as_ref();
+		let with_file = |error: SceneError| SceneError { file: Some(path.to_path_buf()), ..error };
+		let json = std::fs::read_to_string(path)
+			.map_err(|error| with_file(SceneError::new("", error.to_string())))?;
+		Self::parse(&json, path.parent().unwrap_or(Path::new(""))).map_err(with_file)
+	}
+
+	/// Parses a scene description from a JSON string. Files referenced by the scene are looked up relative to the
+	/// current directory.
+	pub fn from_json(json: &str) -> Result<Self, SceneError> {
+		Self::parse(json, Path::new(""))
+	}
+
+	/// Parses a scene description, looking up referenced files relative to `directory`.
+	fn parse(json: &str, directory: &Path) -> Result<Self, SceneError> {
+		let deserializer = &mut serde_json::Deserializer::from_str(json);
+		let description: SceneDescription = serde_path_to_error::deserialize(deserializer)
+			.map_err(|error| {
+				let field = match error.path().to_string().as_str() {
+					"." => String::new(),
+					path => path.to_string(),
+				};
+				SceneError::new(field, error.into_inner().to_string())
+			})?;
+		description.build(directory)
+	}
+}
+
+/// Error in a scene description.
+#[derive(Debug)]
+pub struct SceneError {
+	/// File the scene was loaded from, if any.
+	pub file: Option<PathBuf>,
+	/// Path to the offending field, e.g. `objects[2].radius`. Empty if the error doesn't concern a specific field.
+	pub field: String,
+	/// Description of what is wrong.
+	pub reason: String,
+}
+
+impl SceneError {
+	fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+		Self { file: None, field: field.into(), reason: reason.into() }
+	}
+}
+
+impl fmt::Display for SceneError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if let Some(file) = &self.file {
+			write!(f, "{}: ", file.display())?;
+		}
+		if !self.field.is_empty() {
+			write!(f, "{}: ", self.field)?;
+		}
+		write!(f, "{}", self.reason)
+	}
+}
+
+impl Error for SceneError {}
+
+/// Returns an error for `field` with the `reason` if the `condition` is not met.
+fn ensure(condition: bool, field: impl FnOnce() -> String, reason: &str) -> Result<(), SceneError> {
+	if condition {
+		Ok(())
+	} else {
+		Err(SceneError::new(field(), reason))
+	}
+}
+
+/// Returns an error for `field` if any component of the color is negative or not finite.
+fn ensure_color(color: [f64; 3], field: impl FnOnce() -> String) -> Result<(), SceneError> {
+	ensure(
+		color.iter().all(|component| component.is_finite() && *component >= 0.0),
+		field,
+		"color components must be finite and not negative",
+	)
+}
+
+/// Returns an error for `field` if the `anisotropy` of a phase function is not between -1.0 and 1.0 (exclusive).
+fn ensure_anisotropy(anisotropy: f64, field: impl FnOnce() -> String) -> Result<(), SceneError> {
+	ensure(anisotropy > -1.0 && anisotropy < 1.0, field, "must be between -1 and 1 (exclusive)")
+}
+
+/// Converts a linear color given as red, green and blue components to an [Rgb].
+fn color([r, g, b]: [f64; 3]) -> Rgb {
+	Rgb::new(r, g, b)
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct SceneDescription {
+	#[serde(default)]
+	camera: CameraDescription,
+	#[serde(default)]
+	render: RenderDescription,
+	#[serde(default)]
+	materials: BTreeMap<String, MaterialDescription>,
+	objects: Vec<ObjectDescription>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields, default)]
+struct CameraDescription {
+	look_from: [f64; 3],
+	look_at: [f64; 3],
+	vertical_up: [f64; 3],
+	projection: ProjectionDescription,
+	/// In degrees.
+	vertical_fov: f64,
+	/// Diameter of the lens, 0.0 for a pinhole camera without depth of field.
+	aperture: f64,
+	/// Defaults to the distance between `look_from` and `look_at`.
+	focus_distance: Option<f64>,
+	/// Times at which the shutter opens and closes, between 0.0 and 1.0.
+	shutter: [f64; 2],
+}
+
+impl Default for CameraDescription {
+	fn default() -> Self {
+		Self {
+			look_from: [-2.0, 2.0, 1.0],
+			look_at: [0.0, 0.0, -1.0],
+			vertical_up: [0.0, 1.0, 0.0],
+			projection: ProjectionDescription::Perspective,
+			vertical_fov: 90.0,
+			aperture: 0.0,
+			focus_distance: None,
+			shutter: [0.0, 0.0],
+		}
+	}
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields, default)]
+struct RenderDescription {
+	width: usize,
+	height: usize,
+	samples_per_pixel: usize,
+	max_depth: usize,
+	/// `null` traces every path until it is absorbed or reaches `max_depth`.
+	russian_roulette_depth: Option<usize>,
+	background: BackgroundDescription,
+	fog: Option<FogDescription>,
+	seed: Option<u64>,
+}
+
+impl Default for RenderDescription {
+	fn default() -> Self {
+		let settings = RenderSettings::default();
+		Self {
+			width: settings.width,
+			height: settings.height,
+			samples_per_pixel: settings.samples_per_pixel,
+			max_depth: settings.max_depth,
+			russian_roulette_depth: settings.russian_roulette_depth,
+			background: BackgroundDescription::default(),
+			fog: None,
+			seed: settings.seed,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum ProjectionDescription {
+	Perspective,
+	Orthographic { height: f64 },
+	Fisheye,
+	Equirectangular,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum BackgroundDescription {
+	Solid { color: [f64; 3] },
+	Gradient { bottom: [f64; 3], top: [f64; 3] },
+}
+
+impl Default for BackgroundDescription {
+	fn default() -> Self {
+		BackgroundDescription::Gradient { bottom: [1.0, 1.0, 1.0], top: [0.5, 0.7, 1.0] }
+	}
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FogDescription {
+	density: f64,
+	albedo: [f64; 3],
+	#[serde(default)]
+	anisotropy: f64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+	Lambertian { albedo: TextureDescription },
+	Metal { albedo: TextureDescription, fuzziness: f64 },
+	Dielectric { index_of_refraction: f64 },
+	DiffuseLight { emit: TextureDescription },
+	/// Phase function of a `constant_medium`, scattering in all directions.
+	Isotropic { albedo: TextureDescription },
+	/// Phase function of a `constant_medium`, preferring forward (positive `anisotropy`) or backward scattering.
+	HenyeyGreenstein { albedo: TextureDescription, anisotropy: f64 },
+}
+
+/// Either a plain color or a texture object with its kind as the only key.
+#[derive(Debug)]
+enum TextureDescription {
+	Color([f64; 3]),
+	Pattern(PatternDescription),
+}
+
+impl<'de> Deserialize<'de> for TextureDescription {
+	/// Tells both forms apart by their first token, so that errors inside a texture keep their field path and reason.
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct TextureVisitor;
+
+		impl<'de> Visitor<'de> for TextureVisitor {
+			type Value = TextureDescription;
+
+			fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				f.write_str("a color [r, g, b] or a texture")
+			}
+
+			fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+				Deserialize::deserialize(SeqAccessDeserializer::new(seq)).map(TextureDescription::Color)
+			}
+
+			fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+				Deserialize::deserialize(MapAccessDeserializer::new(map)).map(TextureDescription::Pattern)
+			}
+		}
+
+		deserializer.deserialize_any(TextureVisitor)
+	}
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum PatternDescription {
+	Checker { even: Box<TextureDescription>, odd: Box<TextureDescription>, size: f64 },
+	UvChecker { even: Box<TextureDescription>, odd: Box<TextureDescription>, columns: usize, rows: usize },
+	Image(ImageDescription),
+	Noise(NoiseDescription),
+	Turbulence(NoiseDescription),
+	Marble(NoiseDescription),
+	Wood(NoiseDescription),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ImageDescription {
+	/// PPM or PNG file.
+	file: PathBuf,
+	#[serde(default)]
+	color_space: ColorSpaceDescription,
+	#[serde(default)]
+	filter: FilterDescription,
+	#[serde(default)]
+	wrap: WrapDescription,
+}
+
+/// Transfer function the colors of an image file are encoded with.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+enum ColorSpaceDescription {
+	#[default]
+	Srgb,
+	Linear,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+enum FilterDescription {
+	Nearest,
+	#[default]
+	Bilinear,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+enum WrapDescription {
+	#[default]
+	Repeat,
+	Mirror,
+	Clamp,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields, default)]
+struct NoiseDescription {
+	scale: f64,
+	octaves: usize,
+	seed: u64,
+	low: Box<TextureDescription>,
+	high: Box<TextureDescription>,
+}
+
+impl Default for NoiseDescription {
+	fn default() -> Self {
+		Self {
+			scale: 1.0,
+			octaves: 7,
+			seed: 0,
+			low: Box::new(TextureDescription::Color([0.0, 0.0, 0.0])),
+			high: Box::new(TextureDescription::Color([1.0, 1.0, 1.0])),
+		}
+	}
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDescription {
+	Sphere { center: [f64; 3], radius: f64, material: String },
+	Triangle { vertices: [[f64; 3]; 3], material: String },
+	/// Infinite plane through `point`.
+	Plane { point: [f64; 3], normal: [f64; 3], material: String },
+	/// Parallelogram spanned by two `edges` from `corner`.
+	Quad { corner: [f64; 3], edges: [[f64; 3]; 2], material: String },
+	Disk { center: [f64; 3], normal: [f64; 3], radius: f64, material: String },
+	/// Axis-aligned box.
+	#[serde(rename = "box")]
+	Cuboid { min: [f64; 3], max: [f64; 3], material: String },
+	/// Cylinder around the axis from `start` to `end`, closed at both ends unless `capped` is false.
+	Cylinder { start: [f64; 3], end: [f64; 3], radius: f64, capped: Option<bool>, material: String },
+	/// Cone with its base around `base`, closed unless `capped` is false.
+	Cone { base: [f64; 3], apex: [f64; 3], radius: f64, capped: Option<bool>, material: String },
+	Capsule { start: [f64; 3], end: [f64; 3], radius: f64, material: String },
+	/// Ring around `center` in the plane perpendicular to `axis`.
+	Torus { center: [f64; 3], axis: [f64; 3], major_radius: f64, minor_radius: f64, material: String },
+	Mesh {
+		positions: Vec<[f64; 3]>,
+		#[serde(default)]
+		normals: Vec<[f64; 3]>,
+		#[serde(default)]
+		uvs: Vec<[f64; 2]>,
+		triangles: Vec<[usize; 3]>,
+		material: String,
+	},
+	/// Wavefront OBJ file with the materials of its MTL libraries, unless `material` overrides them.
+	Model { file: PathBuf, material: Option<String> },
+	/// Object moved by `displacement` over the duration of a frame.
+	Moving { displacement: [f64; 3], object: Box<ObjectDescription> },
+	/// Object transformed by the steps of `transform`, in order.
+	Instance { transform: Vec<TransformDescription>, object: Box<ObjectDescription> },
+	/// Smoke or fog filling the convex `boundary` object, whose own material is ignored.
+	ConstantMedium { boundary: Box<ObjectDescription>, density: f64, material: String },
+	/// Inside either of the closed objects.
+	Union { left: Box<ObjectDescription>, right: Box<ObjectDescription> },
+	/// Inside both of the closed objects.
+	Intersection { left: Box<ObjectDescription>, right: Box<ObjectDescription> },
+	/// Inside the closed `left` object, but not the `right` one.
+	Difference { left: Box<ObjectDescription>, right: Box<ObjectDescription> },
+	/// Shape given by a signed distance function, rendered by sphere tracing.
+	Sdf { shape: ShapeDescription, epsilon: Option<f64>, max_steps: Option<usize>, material: String },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum ShapeDescription {
+	Sphere { center: [f64; 3], radius: f64 },
+	/// Axis-aligned box whose edges are rounded off with the `radius`.
+	RoundedBox { min: [f64; 3], max: [f64; 3], radius: f64 },
+	/// Fractal, with a `power` of 8.0 and 10 `iterations` unless given.
+	Mandelbulb { center: [f64; 3], scale: f64, power: Option<f64>, iterations: Option<usize> },
+	/// Shapes blended into each other where they are closer than `smoothness`.
+	SmoothUnion { shapes: Vec<ShapeDescription>, smoothness: f64 },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TransformDescription {
+	Translate([f64; 3]),
+	/// Counter-clockwise around `axis` (through the origin) when looking against it.
+	Rotate { axis: [f64; 3], degrees: f64 },
+	Scale([f64; 3]),
+}
+
+impl SceneDescription {
+	/// Validates the description and builds the scene from it.
+	fn build(self, directory: &Path) -> Result<Scene, SceneError> {
+		let settings = self.render.build()?;
+		let camera = self.camera.build(settings.width as f64 / settings.height as f64)?;
+
+		let materials = self.materials.into_iter()
+			.map(|(name, material)| {
+				let material = material.build(directory, || format!("materials.{}", name))?;
+				Ok((name, material))
+			})
+			.collect::<Result<BTreeMap<_, _>, SceneError>>()?;
+
+		let hittables = self.objects.into_iter()
+			.enumerate()
+			.map(|(index, object)| object.build(&materials, directory, || format!("objects[{}]", index)))
+			.collect::<Result<Vec<_>, SceneError>>()?;
+
+		Ok(Scene { camera, settings, world: Bvh::new(hittables) })
+	}
+}
+
+impl CameraDescription {
+	fn build(self, aspect_ratio: f64) -> Result<Camera, SceneError> {
+		let mut builder = Camera::builder()
+			.aspect_ratio(aspect_ratio)
+			.projection(match &self.projection {
+				ProjectionDescription::Perspective => Projection::Perspective,
+				&ProjectionDescription::Orthographic { height } => Projection::Orthographic { height },
+				ProjectionDescription::Fisheye => Projection::Fisheye,
+				ProjectionDescription::Equirectangular => Projection::Equirectangular,
+			})
+			.vertical_fov(self.vertical_fov)
+			.look_from(Vec3::from(self.look_from))
+			.look_at(Vec3::from(self.look_at))
+			.vertical_up(Vec3::from(self.vertical_up))
+			.aperture(self.aperture)
+			.shutter(self.shutter[0], self.shutter[1]);
+		if let Some(focus_distance) = self.focus_distance {
+			builder = builder.focus_distance(focus_distance);
+		}
+		builder.build().map_err(|error| {
+			let (field, reason) = match error {
+				// The render size is validated before
+				CameraError::AspectRatio => ("render", "must have a positive aspect ratio"),
+				CameraError::VerticalFov => match &self.projection {
+					ProjectionDescription::Fisheye => ("camera.vertical_fov", "must be between 0 and 360 degrees"),
+					_ => ("camera.vertical_fov", "must be between 0 and 180 degrees (exclusive)"),
+				},
+				CameraError::ViewportHeight => ("camera.projection.orthographic.height", "must be positive"),
+				CameraError::LookAt => ("camera.look_at", "must differ from look_from"),
+				CameraError::VerticalUp => {
+					("camera.vertical_up", "must not be the zero vector or parallel to the viewing direction")
+				}
+				CameraError::Aperture => ("camera.aperture", "must be finite and not negative"),
+				CameraError::FocusDistance => ("camera.focus_distance", "must be positive"),
+				CameraError::Shutter => ("camera.shutter", "must open before it closes, both between 0.0 and 1.0"),
+			};
+			SceneError::new(field, reason)
+		})
+	}
+}
+
+impl RenderDescription {
+	fn build(self) -> Result<RenderSettings, SceneError> {
+		ensure(self.width > 0, || "render.width".to_string(), "must be at least 1")?;
+		ensure(self.height > 0, || "render.height".to_string(), "must be at least 1")?;
+		ensure(self.samples_per_pixel > 0, || "render.samples_per_pixel".to_string(), "must be at least 1")?;
+		ensure(self.max_depth > 0, || "render.max_depth".to_string(), "must be at least 1")?;
+		let background = match self.background {
+			BackgroundDescription::Solid { color: solid } => {
+				ensure_color(solid, || "render.background.solid.color".to_string())?;
+				Background::Solid(color(solid))
+			}
+			BackgroundDescription::Gradient { bottom, top } => {
+				ensure_color(bottom, || "render.background.gradient.bottom".to_string())?;
+				ensure_color(top, || "render.background.gradient.top".to_string())?;
+				Background::Gradient { bottom: color(bottom), top: color(top) }
+			}
+		};
+		let fog = self.fog
+			.map(|fog| {
+				ensure(
+					fog.density.is_finite() && fog.density > 0.0,
+					|| "render.fog.density".to_string(),
+					"must be positive",
+				)?;
+				ensure_color(fog.albedo, || "render.fog.albedo".to_string())?;
+				ensure_anisotropy(fog.anisotropy, || "render.fog.anisotropy".to_string())?;
+				Ok(Fog { density: fog.density, albedo: color(fog.albedo), anisotropy: fog.anisotropy })
+			})
+			.transpose()?;
+		Ok(RenderSettings {
+			width: self.width,
+			height: self.height,
+			samples_per_pixel: self.samples_per_pixel,
+			max_depth: self.max_depth,
+			russian_roulette_depth: self.russian_roulette_depth,
+			background,
+			fog,
+			seed: self.seed,
+			..RenderSettings::default()
+		})
+	}
+}
+
+impl MaterialDescription {
+	fn build(self, directory: &Path, field: impl Fn() -> String) -> Result<Arc<dyn Material>, SceneError> {
+		Ok(match self {
+			MaterialDescription::Lambertian { albedo } => {
+				Arc::new(Lambertian { albedo: albedo.build(directory, &|| format!("{}.lambertian.albedo", field()))? })
+			}
+			MaterialDescription::Metal { albedo, fuzziness } => {
+				let albedo = albedo.build(directory, &|| format!("{}.metal.albedo", field()))?;
+				ensure((0.0..=1.0).contains(&fuzziness), || format!("{}.metal.fuzziness", field()), "must be between 0 and 1")?;
+				Arc::new(Metal { albedo, fuzziness })
+			}
+			MaterialDescription::Dielectric { index_of_refraction } => {
+				ensure(
+					index_of_refraction.is_finite() && index_of_refraction != 0.0,
+					|| format!("{}.dielectric.index_of_refraction", field()),
+					"must be finite and not zero",
+				)?;
+				Arc::new(Dielectric { index_of_refraction })
+			}
+			MaterialDescription::DiffuseLight { emit } => {
+				Arc::new(DiffuseLight { emit: emit.build(directory, &|| format!("{}.diffuse_light.emit", field()))? })
+			}
+			MaterialDescription::Isotropic { albedo } => {
+				Arc::new(Isotropic { albedo: albedo.build(directory, &|| format!("{}.isotropic.albedo", field()))? })
+			}
+			MaterialDescription::HenyeyGreenstein { albedo, anisotropy } => {
+				let albedo = albedo.build(directory, &|| format!("{}.henyey_greenstein.albedo", field()))?;
+				ensure_anisotropy(anisotropy, || format!("{}.henyey_greenstein.anisotropy", field()))?;
+				Arc::new(HenyeyGreenstein { albedo, anisotropy })
+			}
+		})
+	}
+}
+
+impl TextureDescription {
+	fn build(self, directory: &Path, field: &dyn Fn() -> String) -> Result<Arc<dyn Texture>, SceneError> {
+		Ok(match self {
+			TextureDescription::Color(rgb) => {
+				ensure_color(rgb, field)?;
+				Arc::new(color(rgb))
+			}
+			TextureDescription::Pattern(PatternDescription::Checker { even, odd, size }) => {
+				ensure(size.is_finite() && size > 0.0, || format!("{}.checker.size", field()), "must be positive")?;
+				Arc::new(Checker {
+					even: even.build(directory, &|| format!("{}.checker.even", field()))?,
+					odd: odd.build(directory, &|| format!("{}.checker.odd", field()))?,
+					size,
+				})
+			}
+			TextureDescription::Pattern(PatternDescription::UvChecker { even, odd, columns, rows }) => {
+				ensure(columns > 0, || format!("{}.uv_checker.columns", field()), "must be positive")?;
+				ensure(rows > 0, || format!("{}.uv_checker.rows", field()), "must be positive")?;
+				Arc::new(UvChecker {
+					even: even.build(directory, &|| format!("{}.uv_checker.even", field()))?,
+					odd: odd.build(directory, &|| format!("{}.uv_checker.odd", field()))?,
+					columns,
+					rows,
+				})
+			}
+			TextureDescription::Pattern(PatternDescription::Image(image)) => {
+				image.build(directory, &|| format!("{}.image", field()))?
+			}
+			TextureDescription::Pattern(PatternDescription::Noise(noise)) => {
+				noise.build(NoisePattern::Noise, directory, &|| format!("{}.noise", field()))?
+			}
+			TextureDescription::Pattern(PatternDescription::Turbulence(noise)) => {
+				noise.build(NoisePattern::Turbulence, directory, &|| format!("{}.turbulence", field()))?
+			}
+			TextureDescription::Pattern(PatternDescription::Marble(noise)) => {
+				noise.build(NoisePattern::Marble, directory, &|| format!("{}.marble", field()))?
+			}
+			TextureDescription::Pattern(PatternDescription::Wood(noise)) => {
+				noise.build(NoisePattern::Wood, directory, &|| format!("{}.wood", field()))?
+			}
+		})
+	}
+}
+
+impl ImageDescription {
+	fn build(self, directory: &Path, field: &dyn Fn() -> String) -> Result<Arc<dyn Texture>, SceneError> {
+		let image = Image::load(directory.join(&self.file))
+			.map_err(|error| SceneError::new(format!("{}.file", field()), format!("{}: {}", self.file.display(), error)))?;
+		let transfer = match self.color_space {
+			ColorSpaceDescription::Srgb => TransferFunction::Srgb,
+			ColorSpaceDescription::Linear => TransferFunction::Linear,
+		};
+		let filter = match self.filter {
+			FilterDescription::Nearest => Filter::Nearest,
+			FilterDescription::Bilinear => Filter::Bilinear,
+		};
+		let wrap = match self.wrap {
+			WrapDescription::Repeat => Wrap::Repeat,
+			WrapDescription::Mirror => Wrap::Mirror,
+			WrapDescription::Clamp => Wrap::Clamp,
+		};
+		Ok(Arc::new(ImageTexture { filter, wrap, ..ImageTexture::from_image(&image, transfer) }))
+	}
+}
+
+impl NoiseDescription {
+	fn build(
+		self,
+		pattern: NoisePattern,
+		directory: &Path,
+		field: &dyn Fn() -> String,
+	) -> Result<Arc<dyn Texture>, SceneError> {
+		ensure(self.scale.is_finite() && self.scale > 0.0, || format!("{}.scale", field()), "must be positive")?;
+		ensure(self.octaves > 0, || format!("{}.octaves", field()), "must be positive")?;
+		Ok(Arc::new(NoiseTexture {
+			perlin: Perlin::new(self.seed),
+			pattern,
+			scale: self.scale,
+			octaves: self.octaves,
+			low: self.low.build(directory, &|| format!("{}.low", field()))?,
+			high: self.high.build(directory, &|| format!("{}.high", field()))?,
+		}))
+	}
+}
+
+impl ShapeDescription {
+	/// Returns the distance function of the shape and a box containing it.
+	fn build(self, field: &dyn Fn() -> String) -> Result<(Arc<DistanceFunction>, Aabb), SceneError> {
+		Ok(match self {
+			ShapeDescription::Sphere { center, radius } => {
+				ensure(radius.is_finite() && radius > 0.0, || format!("{}.sphere.radius", field()), "must be positive")?;
+				let center = Vec3::from(center);
+				let extent = Vec3 { x: radius, y: radius, z: radius };
+				(sdf::sphere(center, radius), Aabb::from_points(center - extent, center + extent))
+			}
+			ShapeDescription::RoundedBox { min, max, radius } => {
+				ensure(
+					(0..3).all(|axis| min[axis] < max[axis]),
+					|| format!("{}.rounded_box.max", field()),
+					"must be larger than min on every axis",
+				)?;
+				let (min, max) = (Vec3::from(min), Vec3::from(max));
+				let extent = max - min;
+				ensure(
+					radius >= 0.0 && 2.0 * radius <= extent.x.min(extent.y).min(extent.z),
+					|| format!("{}.rounded_box.radius", field()),
+					"must be between 0 and half the size of the box",
+				)?;
+				(sdf::rounded_box(min, max, radius), Aabb { min, max })
+			}
+			ShapeDescription::Mandelbulb { center, scale, power, iterations } => {
+				ensure(scale.is_finite() && scale > 0.0, || format!("{}.mandelbulb.scale", field()), "must be positive")?;
+				let power = power.unwrap_or(8.0);
+				ensure(
+					power.is_finite() && power > 1.0,
+					|| format!("{}.mandelbulb.power", field()),
+					"must be greater than 1",
+				)?;
+				let iterations = iterations.unwrap_or(10);
+				ensure(iterations > 0, || format!("{}.mandelbulb.iterations", field()), "must be at least 1")?;
+				let center = Vec3::from(center);
+				let extent = Vec3 { x: 2.0 * scale, y: 2.0 * scale, z: 2.0 * scale };
+				(sdf::mandelbulb(center, scale, power, iterations), Aabb::from_points(center - extent, center + extent))
+			}
+			ShapeDescription::SmoothUnion { shapes, smoothness } => {
+				ensure(!shapes.is_empty(), || format!("{}.smooth_union.shapes", field()), "must not be empty")?;
+				ensure(
+					smoothness.is_finite() && smoothness >= 0.0,
+					|| format!("{}.smooth_union.smoothness", field()),
+					"must not be negative",
+				)?;
+				let (distances, bounds): (Vec<_>, Vec<_>) = shapes.into_iter()
+					.enumerate()
+					.map(|(index, shape)| shape.build(&|| format!("{}.smooth_union.shapes[{}]", field(), index)))
+					.collect::<Result<Vec<_>, SceneError>>()?
+					.into_iter()
+					.unzip();
+				// The blend bulges out by at most a quarter of the smoothness
+				let bulge = Vec3 { x: smoothness / 4.0, y: smoothness / 4.0, z: smoothness / 4.0 };
+				let bounds = bounds.into_iter().reduce(|box1, box2| box1.surrounding(&box2)).unwrap();
+				let bounds = Aabb { min: bounds.min - bulge, max: bounds.max + bulge };
+				(sdf::smooth_union(distances, smoothness), bounds)
+			}
+		})
+	}
+}
+
+impl TransformDescription {
+	fn build(self, field: impl Fn() -> String) -> Result<Transform, SceneError> {
+		Ok(match self {
+			TransformDescription::Translate(offset) => {
+				ensure(
+					offset.iter().all(|component| component.is_finite()),
+					|| format!("{}.translate", field()),
+					"must be finite",
+				)?;
+				Transform::translation(Vec3::from(offset))
+			}
+			TransformDescription::Rotate { axis, degrees } => {
+				let axis = Vec3::from(axis);
+				// Nearly zero axes are rejected as well, since their direction is mostly rounding errors
+				ensure(!axis.is_near_zero(), || format!("{}.rotate.axis", field()), "must not be the zero vector")?;
+				ensure(degrees.is_finite(), || format!("{}.rotate.degrees", field()), "must be finite")?;
+				Transform::rotation(axis, degrees)
+					.ok_or_else(|| SceneError::new(format!("{}.rotate.axis", field()), "must have a finite length"))?
+			}
+			TransformDescription::Scale(factors) => {
+				ensure(
+					factors.iter().all(|factor| factor.is_finite()),
+					|| format!("{}.scale", field()),
+					"must be finite",
+				)?;
+				Transform::scaling(Vec3::from(factors))
+					.ok_or_else(|| SceneError::new(format!("{}.scale", field()), "must not be zero"))?
+			}
+		})
+	}
+}
+
+impl ObjectDescription {
+	fn build(
+		self,
+		materials: &BTreeMap<String, Arc<dyn Material>>,
+		directory: &Path,
+		field: impl Fn() -> String,
+	) -> Result<Box<dyn Hittable>, SceneError> {
+		let material_by_name = |name: &str, material_field: &dyn Fn() -> String| {
+			materials.get(name)
+				.cloned()
+				.ok_or_else(|| SceneError::new(material_field(), format!("unknown material `{}`", name)))
+		};
+		let build_csg = |operation, kind: &str, left: Box<ObjectDescription>, right: Box<ObjectDescription>| {
+			let left_field: &dyn Fn() -> String = &|| format!("{}.{}.left", field(), kind);
+			let left = left.build(materials, directory, left_field)?;
+			let right_field: &dyn Fn() -> String = &|| format!("{}.{}.right", field(), kind);
+			let right = right.build(materials, directory, right_field)?;
+			Ok::<Box<dyn Hittable>, SceneError>(Box::new(Csg { operation, left, right }))
+		};
+		Ok(match self {
+			ObjectDescription::Sphere { center, radius, material } => {
+				ensure(radius.is_finite() && radius > 0.0, || format!("{}.sphere.radius", field()), "must be positive")?;
+				Box::new(Sphere {
+					center: Vec3::from(center),
+					radius,
+					material: material_by_name(&material, &|| format!("{}.sphere.material", field()))?,
+				})
+			}
+			ObjectDescription::Triangle { vertices, material } => {
+				Box::new(Triangle {
+					vertices: vertices.map(Vec3::from),
+					material: material_by_name(&material, &|| format!("{}.triangle.material", field()))?,
+				})
+			}
+			ObjectDescription::Plane { point, normal, material } => {
+				let normal = Vec3::from(normal);
+				ensure(!normal.is_near_zero(), || format!("{}.plane.normal", field()), "must not be the zero vector")?;
+				Box::new(Plane {
+					point: Vec3::from(point),
+					normal: normal.unit_vector(),
+					material: material_by_name(&material, &|| format!("{}.plane.material", field()))?,
+				})
+			}
+			ObjectDescription::Quad { corner, edges, material } => {
+				let edges = edges.map(Vec3::from);
+				ensure(
+					!edges[0].cross(edges[1]).is_near_zero(),
+					|| format!("{}.quad.edges", field()),
+					"must not be zero or parallel",
+				)?;
+				Box::new(Quad {
+					corner: Vec3::from(corner),
+					edges,
+					material: material_by_name(&material, &|| format!("{}.quad.material", field()))?,
+				})
+			}
+			ObjectDescription::Disk { center, normal, radius, material } => {
+				let normal = Vec3::from(normal);
+				ensure(!normal.is_near_zero(), || format!("{}.disk.normal", field()), "must not be the zero vector")?;
+				ensure(radius.is_finite() && radius > 0.0, || format!("{}.disk.radius", field()), "must be positive")?;
+				Box::new(Disk {
+					center: Vec3::from(center),
+					normal: normal.unit_vector(),
+					radius,
+					material: material_by_name(&material, &|| format!("{}.disk.material", field()))?,
+				})
+			}
+			ObjectDescription::Cuboid { min, max, material } => {
+				ensure(
+					(0..3).all(|axis| min[axis] < max[axis]),
+					|| format!("{}.box.max", field()),
+					"must be larger than min on every axis",
+				)?;
+				Box::new(Cuboid {
+					min: Vec3::from(min),
+					max: Vec3::from(max),
+					material: material_by_name(&material, &|| format!("{}.box.material", field()))?,
+				})
+			}
+			ObjectDescription::Cylinder { start, end, radius, capped, material } => {
+				ensure(start != end, || format!("{}.cylinder.end", field()), "must differ from start")?;
+				ensure(radius.is_finite() && radius > 0.0, || format!("{}.cylinder.radius", field()), "must be positive")?;
+				Box::new(Cylinder {
+					start: Vec3::from(start),
+					end: Vec3::from(end),
+					radius,
+					capped: capped.unwrap_or(true),
+					material: material_by_name(&material, &|| format!("{}.cylinder.material", field()))?,
+				})
+			}
+			ObjectDescription::Cone { base, apex, radius, capped, material } => {
+				ensure(base != apex, || format!("{}.cone.apex", field()), "must differ from base")?;
+				ensure(radius.is_finite() && radius > 0.0, || format!("{}.cone.radius", field()), "must be positive")?;
+				Box::new(Cone {
+					base: Vec3::from(base),
+					apex: Vec3::from(apex),
+					radius,
+					capped: capped.unwrap_or(true),
+					material: material_by_name(&material, &|| format!("{}.cone.material", field()))?,
+				})
+			}
+			ObjectDescription::Capsule { start, end, radius, material } => {
+				ensure(start != end, || format!("{}.capsule.end", field()), "must differ from start")?;
+				ensure(radius.is_finite() && radius > 0.0, || format!("{}.capsule.radius", field()), "must be positive")?;
+				Box::new(Capsule {
+					start: Vec3::from(start),
+					end: Vec3::from(end),
+					radius,
+					material: material_by_name(&material, &|| format!("{}.capsule.material", field()))?,
+				})
+			}
+			ObjectDescription::Torus { center, axis, major_radius, minor_radius, material } => {
+				let axis = Vec3::from(axis);
+				ensure(!axis.is_near_zero(), || format!("{}.torus.axis", field()), "must not be the zero vector")?;
+				ensure(
+					major_radius.is_finite() && major_radius > 0.0,
+					|| format!("{}.torus.major_radius", field()),
+					"must be positive",
+				)?;
+				ensure(
+					minor_radius.is_finite() && minor_radius > 0.0,
+					|| format!("{}.torus.minor_radius", field()),
+					"must be positive",
+				)?;
+				Box::new(Torus {
+					center: Vec3::from(center),
+					axis: axis.unit_vector(),
+					major_radius,
+					minor_radius,
+					material: material_by_name(&material, &|| format!("{}.torus.material", field()))?,
+				})
+			}
+			ObjectDescription::Mesh { positions, normals, uvs, triangles, material } => {
+				let material = material_by_name(&material, &|| format!("{}.mesh.material", field()))?;
+				let mesh = TriangleMesh::new(
+					positions.into_iter().map(Vec3::from).collect(),
+					normals.into_iter().map(Vec3::from).collect(),
+					uvs,
+					triangles,
+					material,
+				);
+				Box::new(mesh.map_err(|error| SceneError::new(format!("{}.mesh", field()), error.to_string()))?)
+			}
+			ObjectDescription::Model { file, material } => {
+				let material = material
+					.map(|material| material_by_name(&material, &|| format!("{}.model.material", field())))
+					.transpose()?;
+				let groups = obj::load(directory.join(file))
+					.map_err(|error| SceneError::new(format!("{}.model.file", field()), error.to_string()))?;
+				let meshes = groups.into_iter()
+					.map(|group| match &material {
+						Some(material) => group.mesh.with_material(Arc::clone(material)),
+						None => group.mesh,
+					})
+					.map(|mesh| Box::new(mesh) as Box<dyn Hittable>)
+					.collect();
+				Box::new(Bvh::new(meshes))
+			}
+			ObjectDescription::Moving { displacement, object } => {
+				ensure(
+					displacement.iter().all(|component| component.is_finite()),
+					|| format!("{}.moving.displacement", field()),
+					"must be finite",
+				)?;
+				let object_field: &dyn Fn() -> String = &|| format!("{}.moving.object", field());
+				let object = object.build(materials, directory, object_field)?;
+				Box::new(Moving { object, displacement: Vec3::from(displacement) })
+			}
+			ObjectDescription::Instance { transform, object } => {
+				let transform = transform.into_iter()
+					.enumerate()
+					.try_fold(Transform::IDENTITY, |transform, (index, step)| {
+						let field = || format!("{}.instance.transform[{}]", field(), index);
+						Ok(transform.then(&step.build(field)?))
+					})?;
+				let object_field: &dyn Fn() -> String = &|| format!("{}.instance.object", field());
+				let object = object.build(materials, directory, object_field)?;
+				Box::new(Instance { object: Arc::from(object), transform })
+			}
+			ObjectDescription::ConstantMedium { boundary, density, material } => {
+				ensure(
+					density.is_finite() && density > 0.0,
+					|| format!("{}.constant_medium.density", field()),
+					"must be positive",
+				)?;
+				let phase_function = material_by_name(&material, &|| format!("{}.constant_medium.material", field()))?;
+				let boundary_field: &dyn Fn() -> String = &|| format!("{}.constant_medium.boundary", field());
+				let boundary = boundary.build(materials, directory, boundary_field)?;
+				Box::new(ConstantMedium { boundary, density, phase_function })
+			}
+			ObjectDescription::Union { left, right } => {
+				build_csg(Operation::Union, "union", left, right)?
+			}
+			ObjectDescription::Intersection { left, right } => {
+				build_csg(Operation::Intersection, "intersection", left, right)?
+			}
+			ObjectDescription::Difference { left, right } => {
+				build_csg(Operation::Difference, "difference", left, right)?
+			}
+			ObjectDescription::Sdf { shape, epsilon, max_steps, material } => {
+				let (distance, bounds) = shape.build(&|| format!("{}.sdf.shape", field()))?;
+				let epsilon = epsilon.unwrap_or(1e-4);
+				ensure(epsilon.is_finite() && epsilon > 0.0, || format!("{}.sdf.epsilon", field()), "must be positive")?;
+				let max_steps = max_steps.unwrap_or(256);
+				ensure(max_steps > 0, || format!("{}.sdf.max_steps", field()), "must be at least 1")?;
+				Box::new(Sdf {
+					distance,
+					bounds,
+					epsilon,
+					max_steps,
+					material: material_by_name(&material, &|| format!("{}.sdf.material", field()))?,
+				})
+			}
+		})
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::Ray;
+
+	use super::*;
+
+	/// Returns a scene description with the JSON array of `objects`, which can use the material `white`.
+	fn with_objects(objects: &str) -> String {
+		format!(
+			r#"{{ "materials": {{ "white": {{ "lambertian": {{ "albedo": [1, 1, 1] }} }} }}, "objects": {} }}"#,
+			objects,
+		)
+	}
+
+	/// Asserts that every object is rejected with an error at the field, given relative to the object.
+	#[track_caller]
+	fn assert_invalid_objects(invalid: &[(&str, &str)]) {
+		for (object, field) in invalid {
+			let error = Scene::from_json(&with_objects(&format!("[{}]", object))).unwrap_err();
+			assert_eq!(error.field, format!("objects[0].{}", field));
+		}
+	}
+
+	#[test]
+	fn example_scene() {
+		let scene = Scene::from_json(include_str!("../scenes/example.json")).unwrap();
+		assert_eq!(scene.world.len(), 4);
+		assert_eq!(scene.settings.width, 480);
+		assert_eq!(scene.camera, Camera::default());
+
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		assert_eq!(scene.world.hits(ray, 0.0, f64::INFINITY).map(|hit| hit.t), Some(1.5));
+	}
+
+	#[test]
+	fn lamp_scene() {
+		let scene = Scene::from_json(include_str!("../scenes/lamp.json")).unwrap();
+		assert_eq!(scene.world.len(), 3);
+		assert_eq!(scene.settings.background, Background::Solid(Rgb::BLACK));
+	}
+
+	#[test]
+	fn cornell_box_scene() {
+		let scene = Scene::from_json(include_str!("../scenes/cornell_box.json")).unwrap();
+		assert_eq!(scene.world.len(), 9);
+		let bounding_box = scene.world.bounding_box().unwrap();
+		assert_eq!((bounding_box.min, bounding_box.max), (Vec3::ZERO, Vec3 { x: 555.0, y: 555.0, z: 555.0 }));
+	}
+
+	#[test]
+	fn flat_primitives() {
+		let objects = r#"[
+			{ "plane": { "point": [0.0, 0.0, 0.0], "normal": [0.0, 2.0, 0.0], "material": "white" } },
+			{ "quad": { "corner": [0.0, 1.0, 0.0], "edges": [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]], "material": "white" } },
+			{ "disk": { "center": [0.0, 2.0, 0.0], "normal": [0.0, 1.0, 0.0], "radius": 0.5, "material": "white" } },
+			{ "box": { "min": [0.0, 3.0, 0.0], "max": [1.0, 4.0, 1.0], "material": "white" } }
+		]"#;
+		let scene = Scene::from_json(&with_objects(objects)).unwrap();
+		// The plane is unbounded and is hit from above, through the other objects' holes
+		assert!(scene.world.bounding_box().is_none());
+		let ray = Ray {
+			origin: Vec3 { x: 0.75, y: 10.0, z: 0.75 },
+			direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+			time: 0.0,
+		};
+		assert_eq!(scene.world.hits(ray, 0.0, f64::INFINITY).map(|hit| hit.t), Some(6.0));
+		let ray = Ray { origin: Vec3 { x: -0.75, y: 10.0, z: -0.75 }, ..ray };
+		let hit = scene.world.hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert_eq!((hit.t, hit.normal), (10.0, Vec3 { x: 0.0, y: 1.0, z: 0.0 }));
+
+		let invalid = [
+			(r#"{ "plane": { "point": [0, 0, 0], "normal": [0, 0, 0], "material": "white" } }"#, "plane.normal"),
+			(r#"{ "quad": { "corner": [0, 0, 0], "edges": [[1, 0, 0], [2, 0, 0]], "material": "white" } }"#, "quad.edges"),
+			(r#"{ "disk": { "center": [0, 0, 0], "normal": [0, 1, 0], "radius": 0, "material": "white" } }"#, "disk.radius"),
+			(r#"{ "box": { "min": [0, 0, 0], "max": [1, 0, 1], "material": "white" } }"#, "box.max"),
+		];
+		assert_invalid_objects(&invalid);
+	}
+
+	#[test]
+	fn round_primitives() {
+		let objects = r#"[
+			{ "cylinder": { "start": [0, 0, 0], "end": [0, 1, 0], "radius": 0.5, "capped": false, "material": "white" } },
+			{ "cone": { "base": [2, 0, 0], "apex": [2, 1, 0], "radius": 0.5, "material": "white" } },
+			{ "capsule": { "start": [4, 0, 0], "end": [4, 1, 0], "radius": 0.5, "material": "white" } },
+			{ "torus": { "center": [6, 0, 0], "axis": [0, 0, 2], "major_radius": 1, "minor_radius": 0.25, "material": "white" } }
+		]"#;
+		let scene = Scene::from_json(&with_objects(objects)).unwrap();
+		let bounding_box = scene.world.bounding_box().unwrap();
+		assert!((bounding_box.min - Vec3 { x: -0.5, y: -1.25, z: -0.5 }).is_near_zero());
+		assert!((bounding_box.max - Vec3 { x: 7.25, y: 1.5, z: 0.5 }).is_near_zero());
+		// Down into the open cylinder, onto the capped cone and onto the top of the upright torus
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: 10.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+			time: 0.0,
+		};
+		assert!(scene.world.hits(ray, 0.0, f64::INFINITY).is_none());
+		let ray = Ray { origin: Vec3 { x: 2.0, y: 10.0, z: 0.0 }, ..ray };
+		assert!((scene.world.hits(ray, 0.0, f64::INFINITY).unwrap().t - 9.0).abs() < 1e-9);
+		let ray = Ray { origin: Vec3 { x: 6.0, y: 10.0, z: 0.0 }, ..ray };
+		assert!((scene.world.hits(ray, 0.0, f64::INFINITY).unwrap().t - 8.75).abs() < 1e-9);
+
+		let invalid = [
+			(r#"{ "cylinder": { "start": [0, 0, 0], "end": [0, 0, 0], "radius": 1, "material": "white" } }"#, "cylinder.end"),
+			(r#"{ "cone": { "base": [0, 0, 0], "apex": [0, 1, 0], "radius": -1, "material": "white" } }"#, "cone.radius"),
+			(r#"{ "capsule": { "start": [0, 0, 0], "end": [0, 0, 0], "radius": 1, "material": "white" } }"#, "capsule.end"),
+			(
+				r#"{ "torus": { "center": [0, 0, 0], "axis": [0, 0, 0], "major_radius": 1, "minor_radius": 1, "material": "white" } }"#,
+				"torus.axis",
+			),
+		];
+		assert_invalid_objects(&invalid);
+	}
+
+	#[test]
+	fn defaults() {
+		let scene = Scene::from_json(r#"{ "objects": [] }"#).unwrap();
+		assert!(scene.world.is_empty());
+		assert_eq!(scene.settings, RenderSettings::default());
+
+		// Russian roulette is on by default, and can be switched off
+		let scene = Scene::from_json(r#"{ "render": { "russian_roulette_depth": null }, "objects": [] }"#).unwrap();
+		assert_eq!(scene.settings.russian_roulette_depth, None);
+	}
+
+	#[test]
+	fn syntax_error_path() {
+		let json = r#"{ "objects": [
+			{ "sphere": { "center": [0, 0, 0], "radius": 1, "material": "m" } },
+			{ "sphere": { "center": [0, 0, 0], "radius": "big", "material": "m" } }
+		] }"#;
+		let error = Scene::from_json(json).unwrap_err();
+		assert_eq!(error.field, "objects[1].sphere.radius");
+	}
+
+	#[test]
+	fn unknown_field() {
+		let error = Scene::from_json(r#"{ "render": { "widht": 100 }, "objects": [] }"#).unwrap_err();
+		assert_eq!(error.field, "render.widht");
+		assert!(error.reason.starts_with("unknown field `widht`"));
+	}
+
+	#[test]
+	fn validation_error_path() {
+		let json = r#"{
+			"materials": { "shiny": { "metal": { "albedo": [1, 1, 1], "fuzziness": 0.0 } } },
+			"objects": [
+				{ "sphere": { "center": [0, 0, 0], "radius": 1, "material": "shiny" } },
+				{ "sphere": { "center": [0, 0, 0], "radius": 1, "material": "matte" } }
+			]
+		}"#;
+		let error = Scene::from_json(json).unwrap_err();
+		assert_eq!(error.field, "objects[1].sphere.material");
+		assert_eq!(error.to_string(), "objects[1].sphere.material: unknown material `matte`");
+
+		for field in ["width", "height", "samples_per_pixel", "max_depth"] {
+			let json = format!(r#"{{ "render": {{ "{}": 0 }}, "objects": [] }}"#, field);
+			let error = Scene::from_json(&json).unwrap_err();
+			assert_eq!(error.to_string(), format!("render.{}: must be at least 1", field));
+		}
+
+		let error = Scene::from_json(r#"{ "camera": { "vertical_fov": 180 }, "objects": [] }"#).unwrap_err();
+		assert_eq!(error.field, "camera.vertical_fov");
+
+		let error = Scene::from_json(r#"{ "camera": { "aperture": -1 }, "objects": [] }"#).unwrap_err();
+		assert_eq!(error.field, "camera.aperture");
+
+		let json = r#"{ "camera": { "projection": { "orthographic": { "height": -1 } } }, "objects": [] }"#;
+		assert_eq!(Scene::from_json(json).unwrap_err().field, "camera.projection.orthographic.height");
+
+		let json = r#"{ "camera": { "look_from": [0, 5, 0], "look_at": [0, 0, 0] }, "objects": [] }"#;
+		assert_eq!(Scene::from_json(json).unwrap_err().field, "camera.vertical_up");
+	}
+
+	#[test]
+	fn defocus() {
+		let scene = Scene::from_json(r#"{ "camera": { "aperture": 0.2 }, "objects": [] }"#).unwrap();
+		assert_eq!(scene.camera.lens_radius, 0.1);
+		// Focused on look_at by default
+		assert_eq!(scene.camera.focus_distance, 12f64.sqrt());
+	}
+
+	#[test]
+	fn projection() {
+		let json = r#"{ "camera": { "projection": { "orthographic": { "height": 3 } } }, "objects": [] }"#;
+		let camera = Scene::from_json(json).unwrap().camera;
+		assert_eq!(camera.projection, Projection::Orthographic { height: 3.0 });
+		assert!((camera.vertical.length() - 3.0).abs() < 1e-9);
+
+		let json = r#"{ "camera": { "projection": "fisheye", "vertical_fov": 270 }, "objects": [] }"#;
+		assert_eq!(Scene::from_json(json).unwrap().camera.projection, Projection::Fisheye);
+	}
+
+	#[test]
+	fn motion_blur() {
+		let json = r#"{
+			"camera": { "shutter": [0.0, 1.0] },
+			"materials": { "red": { "lambertian": { "albedo": [0.8, 0.1, 0.1] } } },
+			"objects": [
+				{ "moving": {
+					"displacement": [0.0, 1.0, 0.0],
+					"object": { "sphere": { "center": [0.0, 0.0, -1.0], "radius": 0.5, "material": "red" } }
+				} }
+			]
+		}"#;
+		let scene = Scene::from_json(json).unwrap();
+		assert_eq!((scene.camera.shutter_open, scene.camera.shutter_close), (0.0, 1.0));
+		let bounding_box = scene.world.bounding_box().unwrap();
+		assert_eq!((bounding_box.min.y, bounding_box.max.y), (-0.5, 1.5));
+
+		let json = json.replace("\"material\": \"red\"", "\"material\": \"blue\"");
+		assert_eq!(Scene::from_json(&json).unwrap_err().field, "objects[0].moving.object.sphere.material");
+		let json = r#"{ "camera": { "shutter": [1.0, 0.5] }, "objects": [] }"#;
+		assert_eq!(Scene::from_json(json).unwrap_err().field, "camera.shutter");
+	}
+
+	#[test]
+	fn participating_media() {
+		let json = r#"{
+			"render": { "fog": { "density": 0.05, "albedo": [0.9, 0.9, 0.9] } },
+			"materials": {
+				"smoke": { "isotropic": { "albedo": [0.2, 0.2, 0.2] } },
+				"cloud": { "henyey_greenstein": { "albedo": [1.0, 1.0, 1.0], "anisotropy": 0.8 } }
+			},
+			"objects": [
+				{ "constant_medium": {
+					"boundary": { "sphere": { "center": [0.0, 0.0, -1.0], "radius": 0.5, "material": "smoke" } },
+					"density": 2.0,
+					"material": "cloud"
+				} }
+			]
+		}"#;
+		let scene = Scene::from_json(json).unwrap();
+		assert_eq!(scene.settings.fog, Some(Fog { density: 0.05, albedo: Rgb::new(0.9, 0.9, 0.9), anisotropy: 0.0 }));
+		let bounding_box = scene.world.bounding_box().unwrap();
+		assert_eq!((bounding_box.min.z, bounding_box.max.z), (-1.5, -0.5));
+
+		let invalid = [
+			(json.replace("\"density\": 2.0", "\"density\": 0.0"), "objects[0].constant_medium.density"),
+			(json.replace("0.8", "1.0"), "materials.cloud.henyey_greenstein.anisotropy"),
+			(json.replace("\"density\": 0.05", "\"density\": -1"), "render.fog.density"),
+		];
+		for (json, field) in invalid {
+			assert_eq!(Scene::from_json(&json).unwrap_err().field, field);
+		}
+	}
+
+	#[test]
+	fn csg() {
+		let json = r#"{
+			"materials": { "glass": { "dielectric": { "index_of_refraction": 1.5 } } },
+			"objects": [
+				{ "difference": {
+					"left": { "intersection": {
+						"left": { "sphere": { "center": [0.0, 0.0, -1.5], "radius": 2.0, "material": "glass" } },
+						"right": { "sphere": { "center": [0.0, 0.0, 1.5], "radius": 2.0, "material": "glass" } }
+					} },
+					"right": { "box": { "min": [-3.0, 0.0, -3.0], "max": [3.0, 3.0, 3.0], "material": "glass" } }
+				} }
+			]
+		}"#;
+		// The lower half of a lens
+		let scene = Scene::from_json(json).unwrap();
+		let ray = Ray {
+			origin: Vec3 { x: 0.0, y: -0.1, z: 10.0 },
+			direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			time: 0.0,
+		};
+		let hit = scene.world.hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.point.z - (4.0 - 0.01f64).sqrt() + 1.5).abs() < 1e-9);
+		assert!(scene.world.hits(Ray { origin: Vec3 { x: 0.0, y: 0.1, z: 10.0 }, ..ray }, 0.0, f64::INFINITY).is_none());
+
+		let json = json.replace("[3.0, 3.0, 3.0]", "[3.0, -3.0, 3.0]");
+		assert_eq!(Scene::from_json(&json).unwrap_err().field, "objects[0].difference.right.box.max");
+	}
+
+	#[test]
+	fn sdf() {
+		let json = r#"{
+			"materials": { "red": { "lambertian": { "albedo": [0.8, 0.1, 0.1] } } },
+			"objects": [
+				{ "sdf": {
+					"shape": { "smooth_union": {
+						"shapes": [
+							{ "sphere": { "center": [0.0, 0.0, 0.0], "radius": 1.0 } },
+							{ "rounded_box": { "min": [1.0, -0.5, -0.5], "max": [3.0, 0.5, 0.5], "radius": 0.1 } },
+							{ "mandelbulb": { "center": [6.0, 0.0, 0.0], "scale": 1.0 } }
+						],
+						"smoothness": 0.4
+					} },
+					"epsilon": 0.00001,
+					"material": "red"
+				} }
+			]
+		}"#;
+		let scene = Scene::from_json(json).unwrap();
+		let bounding_box = scene.world.bounding_box().unwrap();
+		assert!((bounding_box.min - Vec3 { x: -1.1, y: -2.1, z: -2.1 }).is_near_zero());
+		assert!((bounding_box.max - Vec3 { x: 8.1, y: 2.1, z: 2.1 }).is_near_zero());
+		// Down onto the top of the sphere, away from the blend
+		let ray = Ray {
+			origin: Vec3 { x: -0.5, y: 5.0, z: 0.0 },
+			direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+			time: 0.0,
+		};
+		let hit = scene.world.hits(ray, 0.0, f64::INFINITY).unwrap();
+		assert!((hit.t - (5.0 - 0.75f64.sqrt())).abs() < 1e-4);
+
+		let invalid = [
+			(json.replace("\"radius\": 0.1", "\"radius\": 0.6"), "smooth_union.shapes[1].rounded_box.radius"),
+			(json.replace("\"scale\": 1.0", "\"scale\": 0.0"), "smooth_union.shapes[2].mandelbulb.scale"),
+		];
+		for (json, field) in invalid {
+			let error = Scene::from_json(&json).unwrap_err();
+			assert_eq!(error.field, format!("objects[0].sdf.shape.{}", field));
+		}
+	}
+
+	#[test]
+	fn instance() {
+		let json = r#"{
+			"materials": { "red": { "lambertian": { "albedo": [0.8, 0.1, 0.1] } } },
+			"objects": [
+				{ "instance": {
+					"transform": [
+						{ "scale": [2.0, 1.0, 1.0] },
+						{ "rotate": { "axis": [0.0, 0.0, 1.0], "degrees": 90.0 } },
+						{ "translate": [0.0, 0.0, -5.0] }
+					],
+					"object": { "sphere": { "center": [0.0, 0.0, 0.0], "radius": 1.0, "material": "red" } }
+				} }
+			]
+		}"#;
+		let scene = Scene::from_json(json).unwrap();
+		// Stretched along x, then turned upright
+		let extent = scene.world.bounding_box().unwrap().extent();
+		assert!((extent - Vec3 { x: 2.0, y: 4.0, z: 2.0 }).length() < 1e-9);
+
+		let invalid = json.replace("[2.0, 1.0, 1.0]", "[2.0, 0.0, 1.0]");
+		assert_eq!(Scene::from_json(&invalid).unwrap_err().field, "objects[0].instance.transform[0].scale");
+		let invalid = json.replace("[0.0, 0.0, 1.0]", "[0.0, 0.0, 0.0]");
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.to_string(), "objects[0].instance.transform[1].rotate.axis: must not be the zero vector");
+		let invalid = json.replace("[0.0, 0.0, 1.0]", "[0.0, 0.0, 1e308]");
+		assert_eq!(Scene::from_json(&invalid).unwrap_err().field, "objects[0].instance.transform[1].rotate.axis");
+	}
+
+	#[test]
+	fn textures() {
+		let json = r#"{
+			"materials": {
+				"floor": { "lambertian": { "albedo": { "checker": {
+					"even": [1, 1, 1],
+					"odd": { "uv_checker": { "even": [0, 0, 0], "odd": [1, 0, 0], "columns": 8, "rows": 4 } },
+					"size": 0.5
+				} } } }
+			},
+			"objects": []
+		}"#;
+		assert!(Scene::from_json(json).is_ok());
+
+		let invalid = json.replace("\"rows\": 4", "\"rows\": 0");
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.field, "materials.floor.lambertian.albedo.checker.odd.uv_checker.rows");
+
+		let marble = json.replace("[1, 1, 1]", r#"{ "marble": { "scale": 4, "high": [0.9, 0.9, 0.8] } }"#);
+		assert!(Scene::from_json(&marble).is_ok());
+		let invalid = json.replace("[1, 1, 1]", r#"{ "wood": { "octaves": 0 } }"#);
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.field, "materials.floor.lambertian.albedo.checker.even.wood.octaves");
+
+		let invalid = json.replace("[1, 1, 1]", "\"white\"");
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.field, "materials.floor.lambertian.albedo.checker.even");
+		assert!(error.reason.contains("a color [r, g, b] or a texture"), "{}", error.reason);
+
+		// Mistakes inside a texture keep their path and reason
+		let invalid = json.replace("\"size\"", "\"sise\"");
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.field, "materials.floor.lambertian.albedo.checker.sise");
+		assert!(error.reason.starts_with("unknown field `sise`"), "{}", error.reason);
+		let invalid = json.replace("[0, 0, 0]", "[0, 0]");
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.field, "materials.floor.lambertian.albedo.checker.odd.uv_checker.even");
+		assert!(error.reason.starts_with("invalid length 2"), "{}", error.reason);
+	}
+
+	#[test]
+	fn image_texture() {
+		let json = r#"{
+			"materials": {
+				"tiled": { "lambertian": { "albedo": { "image": { "file": "scenes/textures/tiles.ppm", "filter": "nearest" } } } }
+			},
+			"objects": []
+		}"#;
+		assert!(Scene::from_json(json).is_ok());
+
+		let missing = json.replace("tiles.ppm", "missing.ppm");
+		let error = Scene::from_json(&missing).unwrap_err();
+		assert_eq!(error.field, "materials.tiled.lambertian.albedo.image.file");
+		assert!(error.reason.starts_with("scenes/textures/missing.ppm: "));
+
+		let invalid = json.replace("nearest", "cubic");
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.field, "materials.tiled.lambertian.albedo.image.filter");
+		assert!(error.reason.starts_with("unknown variant `cubic`"), "{}", error.reason);
+	}
+
+	#[test]
+	fn mesh() {
+		let json = r#"{
+			"materials": { "matte": { "lambertian": { "albedo": [0.5, 0.5, 0.5] } } },
+			"objects": [
+				{ "triangle": { "vertices": [[0, 0, 0], [1, 0, 0], [0, 1, 0]], "material": "matte" } },
+				{ "mesh": {
+					"positions": [[0, 0, -1], [1, 0, -1], [0, 1, -1]],
+					"triangles": [[0, 1, 2]],
+					"material": "matte"
+				} }
+			]
+		}"#;
+		let scene = Scene::from_json(json).unwrap();
+		assert_eq!(scene.world.len(), 2);
+
+		let invalid = json.replace("[[0, 1, 2]]", "[[0, 1, 5]]");
+		let error = Scene::from_json(&invalid).unwrap_err();
+		assert_eq!(error.to_string(), "objects[1].mesh: triangle 0 references vertex 5, which doesn't exist");
+	}
+
+	#[test]
+	fn model() {
+		let scene = Scene::load("scenes/model.json").unwrap();
+		assert_eq!(scene.world.len(), 2);
+
+		let json = r#"{ "objects": [{ "model": { "file": "scenes/models/missing.obj" } }] }"#;
+		let error = Scene::from_json(json).unwrap_err();
+		assert_eq!(error.field, "objects[0].model.file");
+		assert!(error.reason.starts_with("scenes/models/missing.obj: "));
+	}
+
+	#[test]
+	fn missing_file() {
+		let error = Scene::load("does/not/exist.json").unwrap_err();
+		assert_eq!(error.file, Some(PathBuf::from("does/not/exist.json")));
+		assert!(error.to_string().starts_with("does/not/exist.json: "));
+	}
+}
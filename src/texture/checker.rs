@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::{Rgb, Vec3};
+use crate::texture::Texture;
+
+/// Three-dimensional checkerboard of cubes alternating between two textures.
+///
+/// The pattern is defined in world space, so objects look like they were carved from a block of it.
+#[derive(Clone, Debug)]
+pub struct Checker {
+	pub even: Arc<dyn Texture>,
+	pub odd: Arc<dyn Texture>,
+	/// Edge length of the cubes.
+	pub size: f64,
+}
+
+impl Texture for Checker {
+	fn value(&self, uv: [f64; 2], point: Vec3) -> Rgb {
+		let cell = |coordinate: f64| (coordinate / self.size).floor() as i64;
+		if (cell(point.x) + cell(point.y) + cell(point.z)).rem_euclid(2) == 0 {
+			self.even.value(uv, point)
+		} else {
+			self.odd.value(uv, point)
+		}
+	}
+}
+
+/// Checkerboard in texture space, alternating between two textures.
+///
+/// Unlike [Checker], the pattern follows the surface, e.g. it is wrapped around a sphere from pole to pole.
+#[derive(Clone, Debug)]
+pub struct UvChecker {
+	pub even: Arc<dyn Texture>,
+	pub odd: Arc<dyn Texture>,
+	/// Number of squares along `u`.
+	pub columns: usize,
+	/// Number of squares along `v`.
+	pub rows: usize,
+}
+
+impl Texture for UvChecker {
+	fn value(&self, uv: [f64; 2], point: Vec3) -> Rgb {
+		let column = (uv[0] * self.columns as f64).floor() as i64;
+		let row = (uv[1] * self.rows as f64).floor() as i64;
+		if (column + row).rem_euclid(2) == 0 {
+			self.even.value(uv, point)
+		} else {
+			self.odd.value(uv, point)
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn checker() {
+		let checker = Checker { even: Arc::new(Rgb::WHITE), odd: Arc::new(Rgb::BLACK), size: 0.5 };
+		let value = |x, y, z| checker.value([0.0, 0.0], Vec3 { x, y, z });
+		assert_eq!(value(0.25, 0.25, 0.25), Rgb::WHITE);
+		assert_eq!(value(0.75, 0.25, 0.25), Rgb::BLACK);
+		assert_eq!(value(-0.25, 0.25, 0.25), Rgb::BLACK);
+		assert_eq!(value(-0.25, -0.25, 0.25), Rgb::WHITE);
+	}
+
+	#[test]
+	fn uv_checker() {
+		let checker = UvChecker { even: Arc::new(Rgb::WHITE), odd: Arc::new(Rgb::BLACK), columns: 4, rows: 2 };
+		let value = |u, v| checker.value([u, v], Vec3::default());
+		assert_eq!(value(0.1, 0.1), Rgb::WHITE);
+		assert_eq!(value(0.3, 0.1), Rgb::BLACK);
+		assert_eq!(value(0.3, 0.6), Rgb::WHITE);
+	}
+}
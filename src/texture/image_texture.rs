@@ -0,0 +1,150 @@
+use crate::{Framebuffer, Image, Rgb, TransferFunction, Vec3};
+use crate::texture::Texture;
+
+/// How an [ImageTexture] computes the color between the centers of its texels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Filter {
+	/// Color of the nearest texel, which gives a blocky look when magnified.
+	Nearest,
+	/// Linear interpolation between the four nearest texels.
+	#[default]
+	Bilinear,
+}
+
+/// How an [ImageTexture] continues beyond the texture coordinates 0.0 and 1.0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Wrap {
+	/// Repeats the image.
+	#[default]
+	Repeat,
+	/// Repeats the image, mirroring every other copy so that the edges match.
+	Mirror,
+	/// Extends the border texels.
+	Clamp,
+}
+
+/// Texture looking up the colors in an image.
+///
+/// The image covers texture coordinates from 0.0 to 1.0, with `u` running from left to right and `v` from the
+/// bottom to the top.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+	/// Linear colors of the texels.
+	pub texels: Framebuffer,
+	pub filter: Filter,
+	pub wrap: Wrap,
+}
+
+impl ImageTexture {
+	/// Creates a texture with the default [Filter] and [Wrap] mode.
+	pub fn new(texels: Framebuffer) -> Self {
+		Self { texels, filter: Filter::default(), wrap: Wrap::default() }
+	}
+
+	/// Creates a texture from an 8-bit image whose colors are encoded with the `transfer` function, which is
+	/// [TransferFunction::Srgb] for photos and most other images.
+	pub fn from_image(image: &Image, transfer: TransferFunction) -> Self {
+		let decode = |component: u8| transfer.decode(component as f64 / u8::MAX as f64);
+		let texels = Framebuffer {
+			rows: image.rows.iter()
+				.map(|row| row.iter().map(|color| Rgb::new(decode(color.r), decode(color.g), decode(color.b))).collect())
+				.collect(),
+		};
+		Self::new(texels)
+	}
+
+	/// Returns the texel in column `x` and row `y`, which may be outside of the image.
+	fn texel(&self, x: i64, y: i64) -> Rgb {
+		let wrap = |index: i64, size: usize| {
+			let size = size as i64;
+			let index = match self.wrap {
+				Wrap::Repeat => index.rem_euclid(size),
+				Wrap::Mirror => {
+					let index = index.rem_euclid(2 * size);
+					if index < size { index } else { 2 * size - 1 - index }
+				}
+				Wrap::Clamp => index.clamp(0, size - 1),
+			};
+			index as usize
+		};
+		self.texels.rows[wrap(y, self.texels.height())][wrap(x, self.texels.width())]
+	}
+}
+
+impl Texture for ImageTexture {
+	fn value(&self, uv: [f64; 2], _point: Vec3) -> Rgb {
+		let (width, height) = (self.texels.width(), self.texels.height());
+		if width == 0 || height == 0 {
+			return Rgb::BLACK;
+		}
+		// Position in texels; the texture is upside down compared to the image rows
+		let x = uv[0] * width as f64;
+		let y = (1.0 - uv[1]) * height as f64;
+
+		match self.filter {
+			Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+			Filter::Bilinear => {
+				// Relative to the centers of the texels
+				let (x, y) = (x - 0.5, y - 0.5);
+				let (x0, y0) = (x.floor() as i64, y.floor() as i64);
+				let (fx, fy) = (x - x.floor(), y - y.floor());
+				let top = self.texel(x0, y0) * (1.0 - fx) + self.texel(x0 + 1, y0) * fx;
+				let bottom = self.texel(x0, y0 + 1) * (1.0 - fx) + self.texel(x0 + 1, y0 + 1) * fx;
+				top * (1.0 - fy) + bottom * fy
+			}
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use crate::Color;
+
+	use super::*;
+
+	fn texture(filter: Filter, wrap: Wrap) -> ImageTexture {
+		let mut texels = Framebuffer::new(2, 2);
+		texels.rows[0][1] = Rgb::WHITE;
+		ImageTexture { filter, wrap, ..ImageTexture::new(texels) }
+	}
+
+	#[test]
+	fn nearest_texel() {
+		let texture = texture(Filter::Nearest, Wrap::Clamp);
+		// The top right texel
+		assert_eq!(texture.value([0.75, 0.75], Vec3::default()), Rgb::WHITE);
+		assert_eq!(texture.value([1.0, 1.0], Vec3::default()), Rgb::WHITE);
+		assert_eq!(texture.value([0.75, 0.25], Vec3::default()), Rgb::BLACK);
+		assert_eq!(texture.value([2.0, 0.0], Vec3::default()), Rgb::BLACK);
+	}
+
+	#[test]
+	fn wrap() {
+		let value = |wrap, u| texture(Filter::Nearest, wrap).value([u, 0.75], Vec3::default());
+		assert_eq!(value(Wrap::Repeat, 1.75), Rgb::WHITE);
+		assert_eq!(value(Wrap::Repeat, -0.25), Rgb::WHITE);
+		assert_eq!(value(Wrap::Mirror, 1.25), Rgb::WHITE);
+		assert_eq!(value(Wrap::Mirror, 1.75), Rgb::BLACK);
+		assert_eq!(value(Wrap::Clamp, 5.0), Rgb::WHITE);
+		assert_eq!(value(Wrap::Clamp, -5.0), Rgb::BLACK);
+	}
+
+	#[test]
+	fn bilinear() {
+		let texture = texture(Filter::Bilinear, Wrap::Clamp);
+		// At the center of a texel, between two texels and between all four
+		assert_eq!(texture.value([0.75, 0.75], Vec3::default()), Rgb::WHITE);
+		assert_eq!(texture.value([0.5, 0.75], Vec3::default()), Rgb::WHITE * 0.5);
+		assert_eq!(texture.value([0.5, 0.5], Vec3::default()), Rgb::WHITE * 0.25);
+	}
+
+	#[test]
+	fn srgb_to_linear() {
+		let mut image = Image::new(1, 1);
+		image.rows[0][0] = Color { r: 255, g: 188, b: 0 };
+		let texel = ImageTexture::from_image(&image, TransferFunction::Srgb).texels.rows[0][0];
+		assert_eq!((texel.r, texel.b), (1.0, 0.0));
+		assert!((texel.g - 0.5).abs() < 0.01);
+	}
+}
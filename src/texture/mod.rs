@@ -0,0 +1,31 @@
+//! Textures, which vary the colors of materials over the surface of an object.
+
+use std::fmt::Debug;
+
+pub use checker::{Checker, UvChecker};
+pub use image_texture::{Filter, ImageTexture, Wrap};
+pub use noise::{NoisePattern, NoiseTexture};
+pub use perlin::Perlin;
+
+use crate::{Rgb, Vec3};
+
+mod checker;
+mod image_texture;
+mod noise;
+mod perlin;
+
+/// A texture returns a color for every point on a surface.
+///
+/// Textures are shared between the render threads, so they have to be [Send] and [Sync].
+pub trait Texture: Debug + Send + Sync {
+	/// Returns the color at the hit point with the texture coordinates `uv` (see [crate::hittable::Hit::uv]) and
+	/// the position `point` in the world.
+	fn value(&self, uv: [f64; 2], point: Vec3) -> Rgb;
+}
+
+/// A color is a texture with the same value everywhere.
+impl Texture for Rgb {
+	fn value(&self, _uv: [f64; 2], _point: Vec3) -> Rgb {
+		*self
+	}
+}
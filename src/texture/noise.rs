@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use crate::{Rgb, Vec3};
+use crate::texture::{Perlin, Texture};
+
+/// How a [NoiseTexture] turns [Perlin] noise into a pattern.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoisePattern {
+	/// Plain fractional Brownian motion, which looks like clouds.
+	Noise,
+	/// Turbulence, which looks like smoke or fire.
+	Turbulence,
+	/// Stripes along the z axis distorted by turbulence, like the veins of marble.
+	Marble,
+	/// Rings around the y axis slightly distorted by noise, like the grain of a tree trunk.
+	Wood,
+}
+
+/// Procedural texture blending between two textures according to a noise pattern.
+#[derive(Clone, Debug)]
+pub struct NoiseTexture {
+	pub perlin: Perlin,
+	pub pattern: NoisePattern,
+	/// Frequency of the pattern: The number of features per unit length.
+	pub scale: f64,
+	/// Number of noise layers of increasing frequency, which add finer details.
+	pub octaves: usize,
+	/// Texture where the pattern is 0.0.
+	pub low: Arc<dyn Texture>,
+	/// Texture where the pattern is 1.0.
+	pub high: Arc<dyn Texture>,
+}
+
+impl NoiseTexture {
+	/// Returns the value of the pattern at the `point`, between 0.0 and 1.0.
+	pub fn pattern_value(&self, point: Vec3) -> f64 {
+		let point = point * self.scale;
+		let value = match self.pattern {
+			NoisePattern::Noise => 0.5 * (1.0 + self.perlin.fbm(point, self.octaves)),
+			NoisePattern::Turbulence => self.perlin.turbulence(point, self.octaves),
+			NoisePattern::Marble => {
+				0.5 * (1.0 + f64::sin(point.z + 10.0 * self.perlin.turbulence(point, self.octaves)))
+			}
+			NoisePattern::Wood => {
+				let rings = f64::hypot(point.x, point.z) + 2.0 * self.perlin.fbm(point * 0.5, self.octaves);
+				rings - rings.floor()
+			}
+		};
+		value.clamp(0.0, 1.0)
+	}
+}
+
+impl Texture for NoiseTexture {
+	fn value(&self, uv: [f64; 2], point: Vec3) -> Rgb {
+		let t = self.pattern_value(point);
+		self.low.value(uv, point) * (1.0 - t) + self.high.value(uv, point) * t
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn blends_between_textures() {
+		for pattern in [NoisePattern::Noise, NoisePattern::Turbulence, NoisePattern::Marble, NoisePattern::Wood] {
+			let texture = NoiseTexture {
+				perlin: Perlin::new(0),
+				pattern,
+				scale: 3.0,
+				octaves: 5,
+				low: Arc::new(Rgb::new(1.0, 0.0, 0.0)),
+				high: Arc::new(Rgb::new(0.0, 0.0, 1.0)),
+			};
+			for i in 0..100 {
+				let point = Vec3 { x: i as f64 * 0.1, y: 0.5, z: i as f64 * -0.07 };
+				let value = texture.value([0.0, 0.0], point);
+				assert!((value.r + value.b - 1.0).abs() < 1e-9 && value.g == 0.0, "{:?}: {}", pattern, value);
+			}
+		}
+	}
+}
@@ -0,0 +1,146 @@
+//! Perlin noise, a smooth pseudo-random function used for procedural textures.
+
+use std::fmt;
+
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+
+use crate::Vec3;
+
+/// Generator of three-dimensional gradient noise (Ken Perlin's improved noise).
+///
+/// The noise is the same for the same seed, independently of the random numbers used for rendering.
+#[derive(Clone)]
+pub struct Perlin {
+	/// Random permutation of 0..256, repeated once so that hashing needs no wrap-around.
+	permutation: Vec<u8>,
+}
+
+impl Perlin {
+	/// Creates a generator whose noise is determined by the `seed`.
+	pub fn new(seed: u64) -> Self {
+		let mut permutation: Vec<u8> = (0..=u8::MAX).collect();
+		permutation.shuffle(&mut SmallRng::seed_from_u64(seed));
+		permutation.extend_from_within(..);
+		Self { permutation }
+	}
+
+	/// Returns the noise at the `point`, which is roughly between -1.0 and 1.0 and changes smoothly with a feature
+	/// size of about 1.0. It is 0.0 at every point with integer coordinates.
+	pub fn noise(&self, point: Vec3) -> f64 {
+		let cell = |coordinate: f64| (coordinate.floor() as i64 & 0xff) as usize;
+		let (x, y, z) = (cell(point.x), cell(point.y), cell(point.z));
+		let fraction = |coordinate: f64| coordinate - coordinate.floor();
+		let (fx, fy, fz) = (fraction(point.x), fraction(point.y), fraction(point.z));
+		let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+
+		let p = &self.permutation;
+		let hash = |dx: usize, dy: usize, dz: usize| p[p[p[x + dx] as usize + y + dy] as usize + z + dz];
+		// Contribution of the gradient at the corner (dx, dy, dz) of the cell
+		let corner = |dx: usize, dy: usize, dz: usize| {
+			gradient(hash(dx, dy, dz), fx - dx as f64, fy - dy as f64, fz - dz as f64)
+		};
+
+		lerp(
+			w,
+			lerp(v, lerp(u, corner(0, 0, 0), corner(1, 0, 0)), lerp(u, corner(0, 1, 0), corner(1, 1, 0))),
+			lerp(v, lerp(u, corner(0, 0, 1), corner(1, 0, 1)), lerp(u, corner(0, 1, 1), corner(1, 1, 1))),
+		)
+	}
+
+	/// Fractional Brownian motion: The sum of `octaves` layers of noise, each with twice the frequency and half the
+	/// amplitude of the previous one. The result is normalized to roughly -1.0..=1.0.
+	pub fn fbm(&self, point: Vec3, octaves: usize) -> f64 {
+		self.octaves(point, octaves, |noise| noise)
+	}
+
+	/// Like [Perlin::fbm], but sums up the absolute values of the layers, which gives a billowy look with sharp
+	/// creases. The result is roughly in 0.0..=1.0.
+	pub fn turbulence(&self, point: Vec3, octaves: usize) -> f64 {
+		self.octaves(point, octaves, f64::abs)
+	}
+
+	fn octaves(&self, point: Vec3, octaves: usize, layer: impl Fn(f64) -> f64) -> f64 {
+		let mut sum = 0.0;
+		let mut amplitude_sum = 0.0;
+		let mut amplitude = 1.0;
+		let mut frequency = 1.0;
+		for _ in 0..octaves.max(1) {
+			sum += amplitude * layer(self.noise(point * frequency));
+			amplitude_sum += amplitude;
+			amplitude *= 0.5;
+			frequency *= 2.0;
+		}
+		sum / amplitude_sum
+	}
+}
+
+impl fmt::Debug for Perlin {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Perlin").finish_non_exhaustive()
+	}
+}
+
+/// Smoothstep of fifth degree, whose first and second derivatives are zero at 0.0 and 1.0.
+fn fade(t: f64) -> f64 {
+	t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+	a + t * (b - a)
+}
+
+/// Dot product of the offset with one of the twelve gradients pointing to the edges of a cube, chosen by the `hash`.
+fn gradient(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+	match hash % 12 {
+		0 => x + y,
+		1 => -x + y,
+		2 => x - y,
+		3 => -x - y,
+		4 => x + z,
+		5 => -x + z,
+		6 => x - z,
+		7 => -x - z,
+		8 => y + z,
+		9 => -y + z,
+		10 => y - z,
+		_ => -y - z,
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn points() -> impl Iterator<Item = Vec3> {
+		(0..1000).map(|i| Vec3 { x: i as f64 * 0.137, y: i as f64 * 0.071 - 20.0, z: (i % 37) as f64 * 0.29 })
+	}
+
+	#[test]
+	fn deterministic() {
+		let point = Vec3 { x: 0.3, y: 1.7, z: -4.2 };
+		assert_eq!(Perlin::new(7).noise(point), Perlin::new(7).noise(point));
+		assert_ne!(Perlin::new(7).noise(point), Perlin::new(8).noise(point));
+	}
+
+	#[test]
+	fn range() {
+		let perlin = Perlin::new(1);
+		assert_eq!(perlin.noise(Vec3 { x: 3.0, y: -2.0, z: 5.0 }), 0.0);
+		assert!(points().all(|point| perlin.noise(point).abs() <= 1.1));
+		assert!(points().all(|point| perlin.fbm(point, 6).abs() <= 1.1));
+		assert!(points().all(|point| (0.0..=1.1).contains(&perlin.turbulence(point, 6))));
+		// The noise actually varies
+		assert!(points().any(|point| perlin.noise(point) > 0.2));
+		assert!(points().any(|point| perlin.noise(point) < -0.2));
+	}
+
+	#[test]
+	fn smooth() {
+		let perlin = Perlin::new(3);
+		let offset = Vec3 { x: 1e-4, y: 1e-4, z: 1e-4 };
+		assert!(points().all(|point| (perlin.noise(point) - perlin.noise(point + offset)).abs() < 1e-3));
+	}
+}
@@ -0,0 +1,206 @@
+use crate::Vec3;
+
+/// Affine transformation of the 3-dimensional space, i.e. a combination of translations, rotations, scalings and
+/// shears.
+///
+/// It is stored as the upper three rows of a 4×4 matrix, whose last row is always (0, 0, 0, 1), together with its
+/// inverse.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Transform {
+	matrix: [[f64; 4]; 3],
+	inverse: [[f64; 4]; 3],
+}
+
+const IDENTITY: [[f64; 4]; 3] = [
+	[1.0, 0.0, 0.0, 0.0],
+	[0.0, 1.0, 0.0, 0.0],
+	[0.0, 0.0, 1.0, 0.0],
+];
+
+impl Transform {
+	pub const IDENTITY: Transform = Transform { matrix: IDENTITY, inverse: IDENTITY };
+
+	/// Creates the transformation from the upper three rows of its matrix. Returns `None` if the matrix can't be
+	/// inverted, e.g. because it scales an axis by 0.0.
+	pub fn from_matrix(matrix: [[f64; 4]; 3]) -> Option<Transform> {
+		let inverse = invert(matrix)?;
+		Some(Transform { matrix, inverse })
+	}
+
+	/// Moves everything by `offset`.
+	pub fn translation(offset: Vec3) -> Transform {
+		let mut matrix = IDENTITY;
+		let mut inverse = IDENTITY;
+		for axis in 0..3 {
+			matrix[axis][3] = offset[axis];
+			inverse[axis][3] = -offset[axis];
+		}
+		Transform { matrix, inverse }
+	}
+
+	/// Scales everything by the `factors` along the axes, relative to the origin. Returns `None` if a factor is 0.0.
+	pub fn scaling(factors: Vec3) -> Option<Transform> {
+		let mut matrix = IDENTITY;
+		for axis in 0..3 {
+			matrix[axis][axis] = factors[axis];
+		}
+		Self::from_matrix(matrix)
+	}
+
+	/// Rotates everything by `degrees` around the `axis` through the origin, counter-clockwise when looking against
+	/// the axis. Returns `None` if the axis has no direction, i.e. is zero or not finite.
+	pub fn rotation(axis: Vec3, degrees: f64) -> Option<Transform> {
+		let length = axis.length();
+		if !(length.is_finite() && length > 0.0) {
+			return None;
+		}
+		let Vec3 { x, y, z } = axis / length;
+		let (sin, cos) = degrees.to_radians().sin_cos();
+		let matrix = [
+			[cos + x * x * (1.0 - cos), x * y * (1.0 - cos) - z * sin, x * z * (1.0 - cos) + y * sin, 0.0],
+			[y * x * (1.0 - cos) + z * sin, cos + y * y * (1.0 - cos), y * z * (1.0 - cos) - x * sin, 0.0],
+			[z * x * (1.0 - cos) - y * sin, z * y * (1.0 - cos) + x * sin, cos + z * z * (1.0 - cos), 0.0],
+		];
+		// The inverse of a rotation is its transpose
+		let mut inverse = IDENTITY;
+		for row in 0..3 {
+			for column in 0..3 {
+				inverse[row][column] = matrix[column][row];
+			}
+		}
+		Some(Transform { matrix, inverse })
+	}
+
+	/// Returns the transformation that applies `self` first and `next` afterwards.
+	pub fn then(&self, next: &Transform) -> Transform {
+		Transform { matrix: multiply(next.matrix, self.matrix), inverse: multiply(self.inverse, next.inverse) }
+	}
+
+	/// Returns the transformation undoing this one.
+	pub fn inverse(&self) -> Transform {
+		Transform { matrix: self.inverse, inverse: self.matrix }
+	}
+
+	/// Returns the upper three rows of the matrix.
+	pub fn matrix(&self) -> [[f64; 4]; 3] {
+		self.matrix
+	}
+
+	/// Transforms a point, which is affected by all parts of the transformation.
+	pub fn point(&self, point: Vec3) -> Vec3 {
+		apply(&self.matrix, point, 1.0)
+	}
+
+	/// Transforms a direction or offset, which isn't affected by translations.
+	pub fn vector(&self, vector: Vec3) -> Vec3 {
+		apply(&self.matrix, vector, 0.0)
+	}
+
+	/// Transforms a surface normal so that it stays perpendicular to the transformed surface, which takes the
+	/// transpose of the inverse matrix. The result is not normalized.
+	pub fn normal(&self, normal: Vec3) -> Vec3 {
+		let column = |axis: usize| Vec3 { x: self.inverse[0][axis], y: self.inverse[1][axis], z: self.inverse[2][axis] };
+		Vec3 { x: column(0).dot(normal), y: column(1).dot(normal), z: column(2).dot(normal) }
+	}
+}
+
+impl Default for Transform {
+	fn default() -> Self {
+		Transform::IDENTITY
+	}
+}
+
+/// Multiplies the matrix with the vector (x, y, z, w).
+fn apply(matrix: &[[f64; 4]; 3], vector: Vec3, w: f64) -> Vec3 {
+	let row = |row: [f64; 4]| row[0] * vector.x + row[1] * vector.y + row[2] * vector.z + row[3] * w;
+	Vec3 { x: row(matrix[0]), y: row(matrix[1]), z: row(matrix[2]) }
+}
+
+/// Multiplies two affine matrices, so that `b` is applied first.
+fn multiply(a: [[f64; 4]; 3], b: [[f64; 4]; 3]) -> [[f64; 4]; 3] {
+	let mut product = [[0.0; 4]; 3];
+	for row in 0..3 {
+		for column in 0..4 {
+			product[row][column] = (0..3).map(|k| a[row][k] * b[k][column]).sum::<f64>();
+		}
+		product[row][3] += a[row][3];
+	}
+	product
+}
+
+/// Inverts an affine matrix by inverting its linear part with the adjugate and undoing the translation.
+fn invert(matrix: [[f64; 4]; 3]) -> Option<[[f64; 4]; 3]> {
+	let m = |row: usize, column: usize| matrix[row % 3][column % 3];
+	// Cofactors of the 3×3 part, transposed
+	let mut inverse = [[0.0; 4]; 3];
+	for (column, inverse_row) in inverse.iter_mut().enumerate() {
+		for (row, value) in inverse_row.iter_mut().take(3).enumerate() {
+			*value = m(row + 1, column + 1) * m(row + 2, column + 2) - m(row + 1, column + 2) * m(row + 2, column + 1);
+		}
+	}
+	let determinant: f64 = (0..3).map(|column| matrix[0][column] * inverse[column][0]).sum();
+	if determinant.abs() < 1e-12 || !determinant.is_finite() {
+		return None;
+	}
+	for row in inverse.iter_mut() {
+		for value in row.iter_mut().take(3) {
+			*value /= determinant;
+		}
+	}
+	let translation = Vec3 { x: matrix[0][3], y: matrix[1][3], z: matrix[2][3] };
+	let offset = -apply(&inverse, translation, 0.0);
+	for (axis, row) in inverse.iter_mut().enumerate() {
+		row[3] = offset[axis];
+	}
+	Some(inverse)
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn translation_rotation_scaling() {
+		let point = Vec3 { x: 1.0, y: 2.0, z: 3.0 };
+		let offset = Vec3 { x: 1.0, y: -1.0, z: 0.5 };
+		Transform::translation(offset).point(point).assert_near(Vec3 { x: 2.0, y: 1.0, z: 3.5 });
+		Transform::translation(offset).vector(point).assert_near(point);
+
+		// A quarter turn around z turns x into y
+		let rotation = Transform::rotation(Vec3 { x: 0.0, y: 0.0, z: 2.0 }, 90.0).unwrap();
+		rotation.point(Vec3 { x: 1.0, y: 0.0, z: 0.0 }).assert_near(Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+		assert!(Transform::rotation(Vec3::ZERO, 90.0).is_none());
+		assert!(Transform::rotation(Vec3 { x: f64::NAN, y: 0.0, z: 1.0 }, 90.0).is_none());
+
+		let scaling = Transform::scaling(Vec3 { x: 2.0, y: 1.0, z: -1.0 }).unwrap();
+		scaling.point(point).assert_near(Vec3 { x: 2.0, y: 2.0, z: -3.0 });
+		assert!(Transform::scaling(Vec3 { x: 1.0, y: 0.0, z: 1.0 }).is_none());
+	}
+
+	#[test]
+	fn composition_and_inverse() {
+		let transform = Transform::scaling(Vec3 { x: 2.0, y: 2.0, z: 2.0 }).unwrap()
+			.then(&Transform::rotation(Vec3 { x: 1.0, y: 1.0, z: 0.0 }, 30.0).unwrap())
+			.then(&Transform::translation(Vec3 { x: 0.0, y: 0.0, z: -5.0 }));
+		let point = Vec3 { x: 1.0, y: 0.0, z: 0.0 };
+		let scaled = Vec3 { x: 2.0, y: 0.0, z: 0.0 };
+		let rotated = Transform::rotation(Vec3 { x: 1.0, y: 1.0, z: 0.0 }, 30.0).unwrap().point(scaled);
+		transform.point(point).assert_near(rotated + Vec3 { x: 0.0, y: 0.0, z: -5.0 });
+		transform.inverse().point(transform.point(point)).assert_near(point);
+
+		// The inverse computed from the matrix matches the one built up from the parts
+		let inverted = Transform::from_matrix(transform.matrix()).unwrap();
+		inverted.inverse().point(point).assert_near(transform.inverse().point(point));
+	}
+
+	#[test]
+	fn normal() {
+		// Stretching a 45° slope along x flattens it, so its normal tilts towards y
+		let transform = Transform::scaling(Vec3 { x: 2.0, y: 1.0, z: 1.0 }).unwrap();
+		let tangent = Vec3 { x: 1.0, y: 1.0, z: 0.0 };
+		let normal = Vec3 { x: 1.0, y: -1.0, z: 0.0 };
+		assert!(transform.normal(normal).dot(transform.vector(tangent)).abs() < 1e-12);
+		transform.normal(normal).assert_near(Vec3 { x: 0.5, y: -1.0, z: 0.0 });
+	}
+}
@@ -1,6 +1,4 @@
-use rand::Rng;
-
-use crate::Color;
+use crate::{Color, random};
 
 /// Vector in the 3-dimensional space.
 #[derive(Copy, Clone, Default, PartialEq, Debug)]
@@ -45,11 +43,10 @@ impl Vec3 {
 	/// Returns a random vector build of random components in the range [0, 1).
 	/// This is not necessarily a unit vector!
 	pub fn random_of_units() -> Self {
-		let mut rng = rand::thread_rng();
 		Self {
-			x: rng.gen_range(-1.0..1.0),
-			y: rng.gen_range(-1.0..1.0),
-			z: rng.gen_range(-1.0..1.0),
+			x: random::gen_range(-1.0..1.0),
+			y: random::gen_range(-1.0..1.0),
+			z: random::gen_range(-1.0..1.0),
 		}
 	}
 
@@ -66,6 +63,20 @@ impl Vec3 {
 		}
 	}
 
+	/// Creates a random vector in the unit disk in the xy plane, i.e. its length is < 1.0 and its z component is 0.0.
+	pub fn random_in_unit_disk() -> Self {
+		loop {
+			let random_vec = Self {
+				x: random::gen_range(-1.0..1.0),
+				y: random::gen_range(-1.0..1.0),
+				z: 0.0,
+			};
+			if random_vec.squared_length() < 1.0 {
+				return random_vec;
+			}
+		}
+	}
+
 	/// Creates a random vector with length 1.
 	pub fn random_unit_vector() -> Self {
 		Self::random_in_unit_sphere().unit_vector()
@@ -93,6 +104,51 @@ impl Vec3 {
 		let r_out_parallel = normal * -f64::sqrt(f64::abs(1.0 - r_out_perp.squared_length()));
 		r_out_perp + r_out_parallel
 	}
+
+	/// Returns two unit vectors perpendicular to this unit vector and to each other, so that they form a
+	/// right-handed basis with it, i.e. `first.cross(second) == *self`.
+	pub fn orthonormal_basis(&self) -> (Vec3, Vec3) {
+		// Duff et al., "Building an Orthonormal Basis, Revisited", which has no singularity
+		let sign = 1.0f64.copysign(self.z);
+		let a = -1.0 / (sign + self.z);
+		let b = self.x * self.y * a;
+		(
+			Vec3 { x: 1.0 + sign * self.x * self.x * a, y: sign * b, z: -sign * self.x },
+			Vec3 { x: b, y: sign + self.y * self.y * a, z: -self.y },
+		)
+	}
+
+	/// Component-wise minimum.
+	pub fn min(&self, other: Vec3) -> Vec3 {
+		Vec3 {
+			x: f64::min(self.x, other.x),
+			y: f64::min(self.y, other.y),
+			z: f64::min(self.z, other.z),
+		}
+	}
+
+	/// Component-wise maximum.
+	pub fn max(&self, other: Vec3) -> Vec3 {
+		Vec3 {
+			x: f64::max(self.x, other.x),
+			y: f64::max(self.y, other.y),
+			z: f64::max(self.z, other.z),
+		}
+	}
+}
+
+/// Access the components by axis index, i.e. 0 for x, 1 for y and 2 for z.
+impl std::ops::Index<usize> for Vec3 {
+	type Output = f64;
+
+	fn index(&self, axis: usize) -> &f64 {
+		match axis {
+			0 => &self.x,
+			1 => &self.y,
+			2 => &self.z,
+			_ => panic!("Vec3 axis index out of range: {}", axis),
+		}
+	}
 }
 
 impl std::ops::Add for Vec3 {
@@ -161,6 +217,12 @@ impl std::fmt::Display for Vec3 {
 	}
 }
 
+impl From<[f64; 3]> for Vec3 {
+	fn from([x, y, z]: [f64; 3]) -> Vec3 {
+		Vec3 { x, y, z }
+	}
+}
+
 impl From<Color> for Vec3 {
 	fn from(color: Color) -> Vec3 {
 		Vec3 {
@@ -171,6 +233,15 @@ impl From<Color> for Vec3 {
 	}
 }
 
+#[cfg(test)]
+impl Vec3 {
+	/// Asserts that the vector is equal to `expected` up to rounding errors.
+	#[track_caller]
+	pub(crate) fn assert_near(self, expected: Vec3) {
+		assert!((self - expected).length() < 1e-9, "{} != {}", self, expected);
+	}
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -280,6 +351,20 @@ mod tests {
 		assert_eq!(v2.length(), 1.0);
 	}
 
+	#[test]
+	fn min_max() {
+		let v1 = Vec3 { x: 1.0, y: 5.0, z: 3.0 };
+		let v2 = Vec3 { x: 4.0, y: 2.0, z: 6.0 };
+		assert_eq!(v1.min(v2), Vec3 { x: 1.0, y: 2.0, z: 3.0 });
+		assert_eq!(v1.max(v2), Vec3 { x: 4.0, y: 5.0, z: 6.0 });
+	}
+
+	#[test]
+	fn index() {
+		let v1 = Vec3 { x: 1.0, y: 2.0, z: 3.0 };
+		assert_eq!((v1[0], v1[1], v1[2]), (1.0, 2.0, 3.0));
+	}
+
 	#[test]
 	fn from_color() {
 		let color = Color { r: 255, g: 128, b: 64 };
@@ -292,7 +377,7 @@ mod tests {
 	#[test]
 	fn random_in_unit_sphere() {
 		let samples = 100;
-		let randoms = (0..samples).into_iter()
+		let randoms = (0..samples)
 			.map(|_| Vec3::random_in_unit_sphere())
 			.collect::<Vec<Vec3>>();
 
@@ -304,4 +389,18 @@ mod tests {
 		// Not all vectors are simply zero
 		assert!(randoms.iter().all(|vec3| vec3.squared_length() == 0.0).not(), "All random vectors are zero");
 	}
+
+	#[test]
+	fn orthonormal_basis() {
+		for normal in [
+			Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+			Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+			Vec3 { x: 1.0, y: -2.0, z: 3.0 }.unit_vector(),
+		] {
+			let (first, second) = normal.orthonormal_basis();
+			assert!((first.length() - 1.0).abs() < 1e-12 && (second.length() - 1.0).abs() < 1e-12);
+			assert!(first.dot(second).abs() < 1e-12);
+			assert!((first.cross(second) - normal).is_near_zero());
+		}
+	}
 }